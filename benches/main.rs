@@ -8,7 +8,7 @@ mod select_map_utils;
 use criterion::{async_executor::AsyncStdExecutor, criterion_group, criterion_main, Criterion};
 
 pub fn benchmark_select_map(c: &mut Criterion) {
-    for base in vec![8, 32] {
+    for base in [8, 32] {
         c.bench_function(
             format!("SelectMap {base} Stream", base = base).as_str(),
             |b| {
@@ -0,0 +1,208 @@
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[cfg(test)]
+#[path = "./case_test.rs"]
+pub mod case_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Unknown case modifier `{0}`")]
+    UnknownModifier(String),
+    #[error("Unknown case style `{0}`")]
+    UnknownStyle(String),
+    #[error("Unknown name part `{0}`")]
+    UnknownPart(String),
+}
+
+/// Changes the case of a part of the replacement
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Modifier {
+    Upper,
+    Lower,
+    Title,
+}
+
+impl FromStr for Modifier {
+    type Err = Error;
+
+    fn from_str(modifier: &str) -> Result<Self, Self::Err> {
+        match modifier.to_lowercase().as_str() {
+            "upper" => Ok(Self::Upper),
+            "lower" => Ok(Self::Lower),
+            "title" => Ok(Self::Title),
+            _ => Err(Error::UnknownModifier(modifier.to_string())),
+        }
+    }
+}
+
+impl Modifier {
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Self::Upper => text.to_uppercase(),
+            Self::Lower => text.to_lowercase(),
+            Self::Title => title(text),
+        }
+    }
+}
+
+/// capitalizes the first letter of every word and lowers the rest
+fn title(text: &str) -> String {
+    let mut titled = String::with_capacity(text.len());
+    let mut word_start = true;
+    for c in text.chars() {
+        if word_start {
+            titled.extend(c.to_uppercase());
+        } else {
+            titled.extend(c.to_lowercase());
+        }
+        word_start = !c.is_alphanumeric();
+    }
+    titled
+}
+
+/// Naming convention to which a whole name part is converted
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Style {
+    Snake,
+    Kebab,
+    Camel,
+    Pascal,
+    Title,
+    Lower,
+    Upper,
+}
+
+impl FromStr for Style {
+    type Err = Error;
+
+    fn from_str(style: &str) -> Result<Self, Self::Err> {
+        match style.to_lowercase().as_str() {
+            "snake" => Ok(Self::Snake),
+            "kebab" => Ok(Self::Kebab),
+            "camel" => Ok(Self::Camel),
+            "pascal" => Ok(Self::Pascal),
+            "title" => Ok(Self::Title),
+            "lower" => Ok(Self::Lower),
+            "upper" => Ok(Self::Upper),
+            _ => Err(Error::UnknownStyle(style.to_string())),
+        }
+    }
+}
+
+impl Style {
+    pub fn convert(self, text: &str) -> String {
+        let words = split_words(text);
+        match self {
+            Self::Snake => join(&words, Modifier::Lower, Modifier::Lower, "_"),
+            Self::Kebab => join(&words, Modifier::Lower, Modifier::Lower, "-"),
+            Self::Camel => join(&words, Modifier::Lower, Modifier::Title, ""),
+            Self::Pascal => join(&words, Modifier::Title, Modifier::Title, ""),
+            Self::Title => join(&words, Modifier::Title, Modifier::Title, " "),
+            Self::Lower => text.to_lowercase(),
+            Self::Upper => text.to_uppercase(),
+        }
+    }
+}
+
+fn join(words: &[&str], first: Modifier, rest: Modifier, separator: &str) -> String {
+    words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| match index {
+            0 => first.apply(word),
+            _ => rest.apply(word),
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// splits at separators, case boundaries like `fooBar` or `HTTPServer` and digits
+pub fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut word_start = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if !c.is_alphanumeric() {
+            if let Some(start) = word_start.take() {
+                words.push(&text[start..index]);
+            }
+            continue;
+        }
+        let start = *word_start.get_or_insert(index);
+
+        let next = match chars.peek() {
+            Some(&(_, next)) if next.is_alphanumeric() => next,
+            _ => continue,
+        };
+        let is_boundary = (c.is_lowercase() && next.is_uppercase())
+            || (c.is_numeric() != next.is_numeric())
+            || (c.is_uppercase()
+                && next.is_uppercase()
+                && text[index + c.len_utf8() + next.len_utf8()..]
+                    .chars()
+                    .next()
+                    .is_some_and(char::is_lowercase));
+        if is_boundary {
+            let end = index + c.len_utf8();
+            words.push(&text[start..end]);
+            word_start = None;
+        }
+    }
+    if let Some(start) = word_start {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+/// Part of a file name like `.stem.ext`.
+/// A leading dot of hidden files is never a part of them
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Part {
+    Stem,
+    Extension,
+    Name,
+}
+
+impl FromStr for Part {
+    type Err = Error;
+
+    fn from_str(part: &str) -> Result<Self, Self::Err> {
+        match part.to_lowercase().as_str() {
+            "stem" => Ok(Self::Stem),
+            "extension" => Ok(Self::Extension),
+            "name" => Ok(Self::Name),
+            _ => Err(Error::UnknownPart(part.to_string())),
+        }
+    }
+}
+
+impl Part {
+    /// applies `change` only to the selected part of the file name.
+    /// A part without words keeps its text, so that `___.jpg` doesn't become the hidden `.jpg`
+    pub fn map(self, file_name: &str, change: impl Fn(&str) -> String) -> String {
+        let change = |text: &str| match change(text) {
+            changed if changed.is_empty() => text.to_string(),
+            changed => changed,
+        };
+        let hidden = &file_name[..file_name.len() - file_name.trim_start_matches('.').len()];
+        let name = &file_name[hidden.len()..];
+        let (stem, extension) = match name.rfind('.') {
+            Some(dot) => (&name[..dot], Some(&name[dot + 1..])),
+            None => (name, None),
+        };
+
+        match (self, extension) {
+            (Self::Name, _) => format!("{}{}", hidden, change(name)),
+            (Self::Stem, Some(extension)) => format!("{}{}.{}", hidden, change(stem), extension),
+            (Self::Stem, None) => format!("{}{}", hidden, change(stem)),
+            (Self::Extension, Some(extension)) => {
+                format!("{}{}.{}", hidden, stem, change(extension))
+            }
+            (Self::Extension, None) => file_name.to_string(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::*;
+
+#[test]
+fn parse_modifier() {
+    assert_matches!("upper".parse(), Ok(Modifier::Upper));
+    assert_matches!("Lower".parse(), Ok(Modifier::Lower));
+    assert_matches!("TITLE".parse(), Ok(Modifier::Title));
+    assert_matches!("snake".parse::<Modifier>(), Err(Error::UnknownModifier(_)));
+}
+
+#[test]
+fn apply_modifier() {
+    assert_eq!(Modifier::Upper.apply("img_Foo"), "IMG_FOO");
+    assert_eq!(Modifier::Lower.apply("IMG_Foo.JPG"), "img_foo.jpg");
+    assert_eq!(
+        Modifier::Title.apply("hELLO wOrld_foo-bar"),
+        "Hello World_Foo-Bar"
+    );
+}
+
+#[test]
+fn parse_style() {
+    assert_matches!("snake".parse(), Ok(Style::Snake));
+    assert_matches!("Pascal".parse(), Ok(Style::Pascal));
+    assert_matches!("screaming".parse::<Style>(), Err(Error::UnknownStyle(_)));
+}
+
+#[test]
+fn split_words_at_separators() {
+    assert_eq!(
+        split_words("foo_bar-baz qux.x"),
+        vec!["foo", "bar", "baz", "qux", "x"]
+    );
+    assert_eq!(split_words("__foo__"), vec!["foo"]);
+}
+
+#[test]
+fn split_words_at_case_boundaries() {
+    assert_eq!(split_words("fooBarBaz"), vec!["foo", "Bar", "Baz"]);
+    assert_eq!(
+        split_words("HTTPServerError"),
+        vec!["HTTP", "Server", "Error"]
+    );
+    assert_eq!(split_words("ÄrgerÜber"), vec!["Ärger", "Über"]);
+}
+
+#[test]
+fn split_words_at_digits() {
+    assert_eq!(
+        split_words("IMG2021holiday"),
+        vec!["IMG", "2021", "holiday"]
+    );
+}
+
+#[test]
+fn convert_style() {
+    let name = "My fancyHTTPFile_01";
+
+    assert_eq!(Style::Snake.convert(name), "my_fancy_http_file_01");
+    assert_eq!(Style::Kebab.convert(name), "my-fancy-http-file-01");
+    assert_eq!(Style::Camel.convert(name), "myFancyHttpFile01");
+    assert_eq!(Style::Pascal.convert(name), "MyFancyHttpFile01");
+    assert_eq!(Style::Title.convert(name), "My Fancy Http File 01");
+    assert_eq!(Style::Lower.convert(name), "my fancyhttpfile_01");
+    assert_eq!(Style::Upper.convert(name), "MY FANCYHTTPFILE_01");
+}
+
+#[test]
+fn map_part() {
+    let upper = |text: &str| text.to_uppercase();
+
+    assert_eq!(Part::Stem.map("foo.tar.gz", upper), "FOO.TAR.gz");
+    assert_eq!(Part::Extension.map("foo.tar.gz", upper), "foo.tar.GZ");
+    assert_eq!(Part::Name.map("foo.tar.gz", upper), "FOO.TAR.GZ");
+    assert_eq!(Part::Stem.map("foo", upper), "FOO");
+    assert_eq!(Part::Extension.map("foo", upper), "foo");
+}
+
+#[test]
+fn map_part_without_words() {
+    let kebab = |text: &str| Style::Kebab.convert(text);
+
+    assert_eq!(Part::Stem.map("___.jpg", kebab), "___.jpg");
+    assert_eq!(Part::Name.map("___", kebab), "___");
+    assert_eq!(Part::Extension.map("photo.__", kebab), "photo.__");
+    assert_eq!(Part::Stem.map("My Photo.jpg", kebab), "my-photo.jpg");
+}
+
+#[test]
+fn map_part_of_hidden_file() {
+    let snake = |text: &str| Style::Snake.convert(text);
+
+    assert_eq!(Part::Stem.map(".myConfig", snake), ".my_config");
+    assert_eq!(Part::Stem.map(".myConfig.TOML", snake), ".my_config.TOML");
+}
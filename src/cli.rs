@@ -1,7 +1,15 @@
+use crate::case;
+use crate::config::{self, Config};
+use crate::matcher::Selection;
+use crate::normalize;
 use async_std::path::PathBuf;
 use bool_ext::BoolExt;
+use serde::Deserialize;
+use std::num::NonZeroUsize;
 use std::ops::Not;
-use structopt::{clap::AppSettings, StructOpt};
+use std::str::FromStr;
+use structopt::clap::{AppSettings, ArgMatches};
+use structopt::StructOpt;
 
 #[cfg(test)]
 #[path = "./cli_test.rs"]
@@ -11,8 +19,68 @@ pub mod cli_test;
 pub enum Error {
     #[error("Multiple operation modes specified")]
     MultipleOperationModes,
+    #[error("The search and replace patterns are required without another renaming mode")]
+    MissingPatterns,
+    #[error("The positional patterns can't be combined with `-e`")]
+    PatternsWithExpressions,
     #[error("Unknown content `{content}` of environment variable `{var_name}`")]
     UnknownEnvVarContent { var_name: String, content: String },
+    #[error("Unknown sort order `{0}`")]
+    UnknownSortOrder(String),
+    #[error(transparent)]
+    Config(#[from] config::Error),
+}
+
+/// environment variable of the operation mode without `--run` or `--dry-run`
+const DEFAULT_OP_VAR: &str = "FRS_DEFAULT_OP";
+
+/// environment variable of `--icons`
+const SHOW_ICONS_VAR: &str = "FRS_SHOW_ICONS";
+
+/// takes the values of the config, whose options aren't given on the command line.
+/// Clap names the options in kebab-case like their long flags
+macro_rules! set_config_defaults {
+    ($cli:ident, $config:ident, $matches:ident, $($field:ident),+ $(,)?) => {
+        $(
+            if let Some(value) = $config.$field {
+                if !is_on_command_line(&stringify!($field).replace('_', "-"), $matches) {
+                    $cli.$field = value.into();
+                }
+            }
+        )+
+    };
+}
+
+/// options of the config, which are skipped if a conflicting one is on the command line,
+/// as clap only checks the conflicts between the command line options
+const CONFIG_CONFLICTS: &[(&str, &str)] = &[("glob", "fixed-strings"), ("fixed-strings", "glob")];
+
+/// the command line sets the option or one that conflicts with it
+fn is_on_command_line(option: &str, matches: &ArgMatches) -> bool {
+    matches.occurrences_of(option) > 0
+        || CONFIG_CONFLICTS
+            .iter()
+            .any(|&(config, conflict)| config == option && matches.occurrences_of(conflict) > 0)
+}
+
+/// Order in which the files are numbered by the sequence placeholder
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Name,
+    Mtime,
+}
+
+impl FromStr for SortOrder {
+    type Err = Error;
+
+    fn from_str(order: &str) -> Result<Self, Self::Err> {
+        match order.to_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "mtime" => Ok(Self::Mtime),
+            _ => Err(Error::UnknownSortOrder(order.to_string())),
+        }
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -26,6 +94,25 @@ pub struct Cli {
     #[structopt(short, long)]
     pub run: bool,
 
+    /// Confirm every rename with yes, no, all, quit or edit, which types in another name.
+    /// The confirmed renames are run
+    #[structopt(short = "I", long, conflicts_with_all = &["dry-run", "stdin-names"])]
+    pub interactive: bool,
+
+    /// Edit the new paths of all matching files in `$VISUAL` or `$EDITOR`, which are renamed after
+    /// saving. The patterns are optional and transform the paths before
+    #[structopt(long, conflicts_with = "stdin-names")]
+    pub edit: bool,
+
+    /// Read names or paths from stdin and print their new names instead of renaming files.
+    /// Each line of stdin gets one line of stdout, which is unchanged without a match
+    #[structopt(long, conflicts_with = "run")]
+    pub stdin_names: bool,
+
+    /// Show the matches, groups and replacements of every rule for each matched file
+    #[structopt(long)]
+    pub explain: bool,
+
     /// Set the verbosity. In a dry-run its automatically set to 1
     #[structopt(short, long, parse(from_occurrences))]
     pub verbose: u8,
@@ -54,6 +141,36 @@ pub struct Cli {
     #[structopt(short = "i", long)]
     pub case_insensetive: bool,
 
+    /// Write the search patterns as shell globs like `*_*.jpg`.
+    /// Their wildcards are referenced as `$1`, `$2`, … in the replace pattern
+    #[structopt(short, long)]
+    pub glob: bool,
+
+    /// Search for the patterns as plain text instead of a regex
+    #[structopt(short = "F", long, conflicts_with = "glob")]
+    pub fixed_strings: bool,
+
+    /// Use a backtracking regex engine, which supports lookaround and backreferences like `_(?!\d)`.
+    /// It needs frs to be built with the `fancy` feature
+    #[structopt(long)]
+    pub fancy: bool,
+
+    /// Replace only the first matches up to this count in every name
+    #[structopt(long, conflicts_with_all = &["occurrence", "last"])]
+    pub count: Option<NonZeroUsize>,
+
+    /// Replace only the match at this position in every name, starting at 1
+    #[structopt(long, conflicts_with = "last")]
+    pub occurrence: Option<NonZeroUsize>,
+
+    /// Replace only the last match in every name
+    #[structopt(long)]
+    pub last: bool,
+
+    /// Insert the replace patterns as plain text without expanding groups or placeholders like `$1`
+    #[structopt(long)]
+    pub literal_replacement: bool,
+
     #[structopt(
         long,
         default_value = "true",
@@ -62,29 +179,225 @@ pub struct Cli {
     )]
     pub icons: bool,
 
-    pub search_pattern: String,
-    pub replace_pattern: String,
+    /// Fix wrong or missing file extensions based on the detected file content.
+    /// In this mode the patterns are optional
+    #[structopt(short = "x", long)]
+    pub fix_extensions: bool,
+
+    /// Convert the names to a case style. In this mode the patterns are optional
+    #[structopt(
+        long,
+        possible_values = &["snake", "kebab", "camel", "pascal", "title", "lower", "upper"]
+    )]
+    pub case: Option<case::Style>,
+
+    /// Part of the name which gets converted by `--case`
+    #[structopt(
+        long,
+        default_value = "stem",
+        possible_values = &["stem", "extension", "name"]
+    )]
+    pub case_part: case::Part,
+
+    /// Transliterate the names to ASCII and collapse whitespace and punctuation into a separator.
+    /// In this mode the patterns are optional
+    #[structopt(long)]
+    pub slugify: bool,
+
+    /// Separator of the words by `--slugify`
+    #[structopt(long, default_value = "-")]
+    pub slug_separator: String,
+
+    /// Normalize the names to a Unicode normalization form before matching.
+    /// In this mode the patterns are optional
+    #[structopt(long, possible_values = &["nfc", "nfd", "nfkc", "nfkd"])]
+    pub normalize: Option<normalize::Form>,
+
+    /// First number of the sequence placeholder `$n` in the replace pattern
+    #[structopt(long, default_value = "1")]
+    pub sequence_start: u64,
+
+    /// Increment of the sequence placeholder
+    #[structopt(long, default_value = "1")]
+    pub sequence_step: u64,
+
+    /// Restart the sequence in every directory
+    #[structopt(long)]
+    pub sequence_per_dir: bool,
+
+    /// Order in which the files are numbered by the sequence placeholder
+    #[structopt(long, default_value = "name", possible_values = &["name", "mtime"])]
+    pub sort: SortOrder,
+
+    /// Use UTC instead of the local time for date placeholders like `$mtime` or `${now:%Y}`
+    #[structopt(long)]
+    pub utc: bool,
+
+    /// Seed of the random placeholders `$uuid` and `$rand`, so that every run generates the same names
+    #[structopt(long)]
+    pub seed: Option<u64>,
+
+    /// Use this value for EXIF or ID3 placeholders like `${exif:Model}` if the file lacks the tag.
+    /// Without it, such files fail with an error
+    #[structopt(long)]
+    pub tag_fallback: Option<String>,
+
+    /// Apply a search pattern and its replacement. It can be repeated to chain several rules,
+    /// which are applied in order. Then the only positional argument is the base path
+    #[structopt(
+        short = "e",
+        long = "expression",
+        number_of_values = 2,
+        value_names = &["search", "replace"]
+    )]
+    pub expressions: Vec<String>,
+
+    /// Apply the rules of a TOML or YAML file after the ones of the command line.
+    /// Then the only positional argument is the base path
+    #[structopt(long)]
+    pub rules: Option<PathBuf>,
+
+    pub search_pattern: Option<String>,
+    pub replace_pattern: Option<String>,
 
     #[structopt(default_value = ".")]
     pub base_path: PathBuf,
 }
 
 impl Cli {
+    /// applies the defaults of the user config file.
+    /// The command line takes precedence over the environment variables and they over the config
+    pub fn load_config(&mut self, matches: &ArgMatches) -> Result<(), Error> {
+        if let Some(path) = Config::path() {
+            self.set_config_defaults(Config::load(&path)?, matches);
+        }
+        Ok(())
+    }
+
+    fn set_config_defaults(&mut self, config: Config, matches: &ArgMatches) {
+        let has_mode = self.run
+            || self.dry_run
+            || self.interactive
+            || std::env::var_os(DEFAULT_OP_VAR).is_some();
+        match config.mode {
+            Some(config::Mode::Run) if !has_mode => self.run = true,
+            Some(config::Mode::DryRun) if !has_mode => self.dry_run = true,
+            _ => (),
+        }
+        if std::env::var_os(SHOW_ICONS_VAR).is_none() {
+            set_config_defaults!(self, config, matches, icons);
+        }
+        set_config_defaults!(
+            self,
+            config,
+            matches,
+            verbose,
+            continue_on_error,
+            traverse_tree,
+            file,
+            directory,
+            symlink,
+            case_insensetive,
+            glob,
+            fixed_strings,
+            fancy,
+            literal_replacement,
+            fix_extensions,
+            case,
+            case_part,
+            slugify,
+            slug_separator,
+            normalize,
+            sequence_start,
+            sequence_step,
+            sequence_per_dir,
+            sort,
+            utc,
+            seed,
+            tag_fallback,
+        );
+        if let Some(rules) = config.rules {
+            if matches.occurrences_of("rules") == 0 {
+                self.rules = Some(rules.into());
+            }
+        }
+    }
+
     /// does all the automations after clap
     pub fn post_automations(&mut self) -> Result<(), Error> {
+        self.set_patterns()?;
         self.set_operation_mode()?;
         self.set_verbosity();
         self.set_types();
         Ok(())
     }
 
-    /// checks and changes the running option according the environment varaiable
+    /// the patterns are only optional with another renaming mode.
+    /// Then a single positional argument is the base path
+    fn set_patterns(&mut self) -> Result<(), Error> {
+        if !self.expressions.is_empty() {
+            self.replace_pattern
+                .is_none()
+                .err(Error::PatternsWithExpressions)?;
+        } else if self.replace_pattern.is_some() {
+            return Ok(());
+        }
+        self.has_pattern_free_mode().err(Error::MissingPatterns)?;
+
+        if let Some(base_path) = self.search_pattern.take() {
+            self.base_path = base_path.into();
+        }
+        Ok(())
+    }
+
+    /// the matches in every name, which are replaced
+    pub fn selection(&self) -> Selection {
+        match (self.count, self.occurrence) {
+            _ if self.last => Selection::Last,
+            (_, Some(occurrence)) => Selection::Nth(occurrence.get()),
+            (Some(count), _) => Selection::First(count.get()),
+            (None, None) => Selection::All,
+        }
+    }
+
+    /// the pairs of search and replace patterns in the order of application
+    pub fn patterns(&self) -> Vec<(&str, &str)> {
+        let positional = self
+            .search_pattern
+            .as_deref()
+            .map(|search| (search, self.replace_pattern.as_deref().unwrap_or_default()));
+        positional
+            .into_iter()
+            .chain(
+                self.expressions
+                    .chunks(2)
+                    .map(|rule| (rule[0].as_str(), rule[1].as_str())),
+            )
+            .collect()
+    }
+
+    /// these renaming modes don't need a pattern.
+    /// The traversal can find the rules in `.frsrules` files and the paths can be edited
+    fn has_pattern_free_mode(&self) -> bool {
+        self.fix_extensions
+            || self.case.is_some()
+            || self.slugify
+            || self.normalize.is_some()
+            || !self.expressions.is_empty()
+            || self.rules.is_some()
+            || self.traverse_tree
+            || self.edit
+    }
+
+    /// checks and changes the running option according the environment varaiable.
+    /// The interactive mode runs the confirmed renames
     fn set_operation_mode(&mut self) -> Result<(), Error> {
+        self.run |= self.interactive;
         (self.run && self.dry_run)
             .not()
             .err(Error::MultipleOperationModes)?;
 
-        let do_var_name = "FRS_DEFAULT_OP";
+        let do_var_name = DEFAULT_OP_VAR;
         match std::env::var(do_var_name)
             .unwrap_or_else(|_| "DRY-RUN".to_string())
             .to_uppercase()
@@ -110,9 +423,10 @@ impl Cli {
         Ok(())
     }
 
-    /// dry-run sets automatically a minimal verbosity of one
+    /// dry-run sets automatically a minimal verbosity of one.
+    /// The names of stdin are printed without a summary
     fn set_verbosity(&mut self) {
-        self.verbose = self.verbose.max(self.dry_run as u8);
+        self.verbose = self.verbose.max((self.dry_run && !self.stdin_names) as u8);
     }
 
     /// if no type is selected, all are selected
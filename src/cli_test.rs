@@ -7,18 +7,90 @@ pub fn empty_cli() -> Cli {
         dry_run: false,
         continue_on_error: false,
         case_insensetive: false,
+        glob: false,
+        fixed_strings: false,
+        fancy: false,
+        count: None,
+        occurrence: None,
+        last: false,
+        literal_replacement: false,
         file: false,
         directory: false,
         symlink: false,
         traverse_tree: false,
+        interactive: false,
+        edit: false,
+        stdin_names: false,
+        explain: false,
         verbose: 0,
         icons: false,
-        search_pattern: String::new(),
-        replace_pattern: String::new(),
+        fix_extensions: false,
+        case: None,
+        case_part: case::Part::Stem,
+        slugify: false,
+        slug_separator: "-".to_string(),
+        normalize: None,
+        sequence_start: 1,
+        sequence_step: 1,
+        sequence_per_dir: false,
+        sort: SortOrder::Name,
+        utc: false,
+        seed: None,
+        tag_fallback: None,
+        expressions: Vec::new(),
+        rules: None,
+        search_pattern: None,
+        replace_pattern: None,
         base_path: PathBuf::new(),
     }
 }
 
+#[test]
+fn missing_patterns() {
+    let mut cli = empty_cli();
+    cli.search_pattern = Some("dir".to_string());
+
+    assert_matches!(cli.set_patterns(), Err(Error::MissingPatterns));
+}
+
+#[test]
+fn given_patterns() {
+    let mut cli = empty_cli();
+    cli.search_pattern = Some("(.+)".to_string());
+    cli.replace_pattern = Some("${1}".to_string());
+
+    assert_matches!(cli.set_patterns(), Ok(()));
+    assert_eq!(cli.search_pattern, Some("(.+)".to_string()));
+    assert_eq!(cli.base_path, PathBuf::new());
+}
+
+#[test]
+fn optional_patterns_with_base_path() {
+    let mut cli = empty_cli();
+    cli.fix_extensions = true;
+    cli.search_pattern = Some("dir".to_string());
+
+    assert_matches!(cli.set_patterns(), Ok(()));
+    assert_eq!(cli.search_pattern, None);
+    assert_eq!(cli.base_path, PathBuf::from("dir"));
+}
+
+#[test]
+fn parse_sort_order() {
+    assert_matches!("name".parse(), Ok(SortOrder::Name));
+    assert_matches!("MTime".parse(), Ok(SortOrder::Mtime));
+    assert_matches!("size".parse::<SortOrder>(), Err(Error::UnknownSortOrder(_)));
+}
+
+#[test]
+fn optional_patterns_with_case() {
+    let mut cli = empty_cli();
+    cli.case = Some(case::Style::Snake);
+
+    assert_matches!(cli.set_patterns(), Ok(()));
+    assert_eq!(cli.search_pattern, None);
+}
+
 #[test]
 fn multiple_operation_modes() {
     let mut cli = empty_cli();
@@ -110,6 +182,24 @@ fn default_operation_dry_run_with_dry_run() {
     assert!(cli.dry_run);
 }
 
+#[test]
+#[serial(env_var)]
+fn interactive_operation_with_default_dry_run() {
+    let mut cli = empty_cli();
+    cli.interactive = true;
+    std::env::set_var("FRS_DEFAULT_OP", "dry-run");
+
+    assert_matches!(cli.set_operation_mode(), Ok(()));
+    assert!(cli.run);
+    assert!(!cli.dry_run);
+}
+
+#[test]
+fn interactive_conflicts() {
+    assert!(Cli::from_iter_safe(&["frs", "-I", "-n", "a", "b"]).is_err());
+    assert!(Cli::from_iter_safe(&["frs", "-I", "--stdin-names", "a", "b"]).is_err());
+}
+
 #[test]
 fn low_verbosity_on_run() {
     let mut cli = empty_cli();
@@ -146,6 +236,16 @@ fn high_verbosity_on_dry_run() {
     assert_eq!(cli.verbose, 10);
 }
 
+#[test]
+fn no_verbosity_on_stdin_names() {
+    let mut cli = empty_cli();
+    cli.dry_run = true;
+    cli.stdin_names = true;
+
+    cli.set_verbosity();
+    assert_eq!(cli.verbose, 0);
+}
+
 #[test]
 fn no_filetype_set() {
     let mut cli = empty_cli();
@@ -241,3 +341,153 @@ fn not_dir_filetypes_set_with_traverse_tree() {
     assert!(!cli.directory);
     assert!(cli.symlink);
 }
+
+#[test]
+fn expressions_with_base_path() {
+    let mut cli = empty_cli();
+    cli.expressions = vec!["a".to_string(), "b".to_string()];
+    cli.search_pattern = Some("dir".to_string());
+
+    assert_matches!(cli.set_patterns(), Ok(()));
+    assert_eq!(cli.base_path, PathBuf::from("dir"));
+    assert_eq!(cli.patterns(), vec![("a", "b")]);
+}
+
+#[test]
+fn expressions_with_patterns() {
+    let mut cli = empty_cli();
+    cli.expressions = vec!["a".to_string(), "b".to_string()];
+    cli.search_pattern = Some("(.+)".to_string());
+    cli.replace_pattern = Some("${1}".to_string());
+
+    assert_matches!(cli.set_patterns(), Err(Error::PatternsWithExpressions));
+}
+
+#[test]
+fn parse_expressions() {
+    let mut cli = Cli::from_iter(&["frs", "-e", "a", "b", "-e", "c", "d", "dir"]);
+
+    assert_matches!(cli.set_patterns(), Ok(()));
+    assert_eq!(cli.patterns(), vec![("a", "b"), ("c", "d")]);
+    assert_eq!(cli.base_path, PathBuf::from("dir"));
+}
+
+fn parse_with_config(args: &[&str], config: &str) -> Cli {
+    let matches = Cli::clap().get_matches_from(args);
+    let mut cli = Cli::from_clap(&matches);
+    cli.set_config_defaults(toml::from_str(config).unwrap(), &matches);
+    cli
+}
+
+#[test]
+#[serial(env_var)]
+fn config_defaults() {
+    std::env::remove_var("FRS_DEFAULT_OP");
+    std::env::remove_var("FRS_SHOW_ICONS");
+    let cli = parse_with_config(
+        &["frs", "--sort", "name", "a", "b"],
+        r#"
+mode = "run"
+icons = false
+verbose = 1
+sort = "mtime"
+sequence-start = 0
+"#,
+    );
+
+    assert!(cli.run);
+    assert!(!cli.icons);
+    assert_eq!(cli.verbose, 1);
+    assert_eq!(cli.sort, SortOrder::Name);
+    assert_eq!(cli.sequence_start, 0);
+}
+
+#[test]
+#[serial(env_var)]
+fn env_over_config() {
+    std::env::set_var("FRS_DEFAULT_OP", "DRY-RUN");
+    std::env::set_var("FRS_SHOW_ICONS", "true");
+    let cli = parse_with_config(&["frs", "a", "b"], "mode = \"run\"\nicons = false");
+    std::env::remove_var("FRS_DEFAULT_OP");
+    std::env::remove_var("FRS_SHOW_ICONS");
+
+    assert!(!cli.run);
+    assert!(cli.icons);
+}
+
+#[test]
+#[serial(env_var)]
+fn cli_over_config_multi_word_options() {
+    std::env::remove_var("FRS_DEFAULT_OP");
+    let cli = parse_with_config(
+        &[
+            "frs",
+            "--slug-separator",
+            "+",
+            "--sequence-start",
+            "5",
+            "--continue-on-error",
+            "a",
+            "b",
+        ],
+        r#"
+slug-separator = "_"
+sequence-start = 100
+sequence-step = 10
+continue-on-error = false
+"#,
+    );
+
+    assert_eq!(cli.slug_separator, "+");
+    assert_eq!(cli.sequence_start, 5);
+    assert_eq!(cli.sequence_step, 10);
+    assert!(cli.continue_on_error);
+}
+
+#[test]
+#[serial(env_var)]
+fn cli_over_config_mode() {
+    std::env::remove_var("FRS_DEFAULT_OP");
+    let cli = parse_with_config(&["frs", "--dry-run", "a", "b"], "mode = \"run\"");
+
+    assert!(!cli.run);
+    assert!(cli.dry_run);
+}
+
+#[test]
+fn cli_over_config_conflicts() {
+    let cli = parse_with_config(&["frs", "-F", "a_", "x", "d"], "glob = true");
+
+    assert!(cli.fixed_strings);
+    assert!(!cli.glob);
+
+    let cli = parse_with_config(&["frs", "--glob", "*_", "x", "d"], "fixed-strings = true");
+
+    assert!(cli.glob);
+    assert!(!cli.fixed_strings);
+}
+
+#[test]
+fn match_selection() {
+    let mut cli = empty_cli();
+    assert_eq!(cli.selection(), Selection::All);
+
+    cli.count = NonZeroUsize::new(2);
+    assert_eq!(cli.selection(), Selection::First(2));
+
+    cli.count = None;
+    cli.occurrence = NonZeroUsize::new(3);
+    assert_eq!(cli.selection(), Selection::Nth(3));
+
+    cli.occurrence = None;
+    cli.last = true;
+    assert_eq!(cli.selection(), Selection::Last);
+}
+
+#[test]
+fn zero_count() {
+    assert_matches!(
+        Cli::clap().get_matches_from_safe(["frs", "--count", "0", "a", "b"]),
+        Err(_)
+    );
+}
@@ -0,0 +1,97 @@
+use crate::case;
+use crate::cli::SortOrder;
+use crate::normalize;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+#[path = "./config_test.rs"]
+pub mod config_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("The config file `{}` is unreadable: {source}", .path.to_string_lossy())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("The config file `{}` is invalid: {source}", .path.to_string_lossy())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+/// Operation mode, if neither `--run` nor `--dry-run` is given
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    Run,
+    DryRun,
+}
+
+/// Defaults of the command line options.
+/// The keys are named like the long options
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub mode: Option<Mode>,
+    pub verbose: Option<u8>,
+    pub continue_on_error: Option<bool>,
+    pub traverse_tree: Option<bool>,
+    pub file: Option<bool>,
+    pub directory: Option<bool>,
+    pub symlink: Option<bool>,
+    #[serde(alias = "case-insensitive")]
+    pub case_insensetive: Option<bool>,
+    pub glob: Option<bool>,
+    pub fixed_strings: Option<bool>,
+    pub fancy: Option<bool>,
+    pub literal_replacement: Option<bool>,
+    pub icons: Option<bool>,
+    pub fix_extensions: Option<bool>,
+    pub case: Option<case::Style>,
+    pub case_part: Option<case::Part>,
+    pub slugify: Option<bool>,
+    pub slug_separator: Option<String>,
+    pub normalize: Option<normalize::Form>,
+    pub sequence_start: Option<u64>,
+    pub sequence_step: Option<u64>,
+    pub sequence_per_dir: Option<bool>,
+    pub sort: Option<SortOrder>,
+    pub utc: Option<bool>,
+    pub seed: Option<u64>,
+    pub tag_fallback: Option<String>,
+    pub rules: Option<PathBuf>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/frs/config.toml` with a fallback to `~/.config`
+    pub fn path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|config_home| !config_home.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("frs").join("config.toml"))
+    }
+
+    /// a missing config file has no defaults
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(source) => {
+                return Err(Error::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        toml::from_str(&content).map_err(|source| Error::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use super::*;
+use crate::utils::TempDir;
+
+#[test]
+fn parse_config() {
+    let config: Config = toml::from_str(
+        r#"
+mode = "run"
+icons = false
+verbose = 2
+case-insensitive = true
+sort = "mtime"
+slug-separator = "_"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            mode: Some(Mode::Run),
+            icons: Some(false),
+            verbose: Some(2),
+            case_insensetive: Some(true),
+            sort: Some(SortOrder::Mtime),
+            slug_separator: Some("_".to_string()),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn parse_unknown_option() {
+    assert_matches!(toml::from_str::<Config>("hidden = true"), Err(_));
+}
+
+#[test]
+fn load_missing_config() {
+    assert_matches!(
+        Config::load(Path::new("non_existant/config.toml")),
+        Ok(config) if config == Config::default()
+    );
+}
+
+#[test]
+fn load_invalid_config() {
+    let dir = TempDir::new();
+    let path = dir.file("config.toml", "mode = \"fast\"");
+
+    assert_matches!(Config::load(&path), Err(Error::Parse { .. }));
+}
@@ -0,0 +1,146 @@
+use crate::fs::RenameInfo;
+use async_std::path::PathBuf;
+use bool_ext::BoolExt;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::{env, fs, io};
+
+#[cfg(test)]
+#[path = "./edit_test.rs"]
+pub mod edit_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("The editor `{editor}` failed: {source}")]
+    Editor { editor: String, source: io::Error },
+    #[error("The editor `{0}` exited without success")]
+    EditorFailed(String),
+    #[error("The path `{}` isn't valid UTF-8", .0.to_string_lossy())]
+    Utf8Invalid(PathBuf),
+    #[error("Line {0} doesn't start with a number and a tab")]
+    InvalidLine(usize),
+    #[error("Line {line} has the unknown number {number}")]
+    UnknownNumber { line: usize, number: usize },
+    #[error("Line {line} repeats the number {number}")]
+    DuplicateNumber { line: usize, number: usize },
+    #[error("Line {0} has no path")]
+    EmptyPath(usize),
+}
+
+/// environment variables of the editor in the order of precedence
+const EDITOR_VARS: [&str; 2] = ["VISUAL", "EDITOR"];
+
+/// editor without the environment variables
+const DEFAULT_EDITOR: &str = "vi";
+
+const HEADER: &str = "# Edit the paths after the numbers. Removed lines aren't renamed\n";
+
+/// lets the user edit the new paths of the renames in the editor.
+/// The renames of removed lines are dropped
+pub fn edit(renames: Vec<RenameInfo>) -> Result<Vec<RenameInfo>, Error> {
+    let file = create_plan_file(&write_plan(&renames)?)?;
+    let edited = open_editor(&file).and_then(|_| Ok(fs::read_to_string(&file)?));
+    let _ = fs::remove_file(&file);
+    parse_plan(&edited?, renames)
+}
+
+/// writes the plan to a new file with a random name, which only the user can read.
+/// An existing file or symlink of another user is never opened
+fn create_plan_file(plan: &str) -> io::Result<std::path::PathBuf> {
+    loop {
+        let path = env::temp_dir().join(format!("frs-edit-{:016x}.txt", rand::random::<u64>()));
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(plan.as_bytes())?;
+                return Ok(path);
+            }
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => (),
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// one numbered line for every rename, where the number is its position starting at 1
+pub fn write_plan(renames: &[RenameInfo]) -> Result<String, Error> {
+    let mut plan = HEADER.to_string();
+    for (index, rename_info) in renames.iter().enumerate() {
+        let new_path = rename_info
+            .new_path
+            .to_str()
+            .ok_or_else(|| Error::Utf8Invalid(rename_info.new_path.clone()))?;
+        plan.push_str(&format!("{}\t{}\n", index + 1, new_path));
+    }
+    Ok(plan)
+}
+
+/// the renames to the edited paths. Empty lines and `#` comments are ignored
+pub fn parse_plan(plan: &str, renames: Vec<RenameInfo>) -> Result<Vec<RenameInfo>, Error> {
+    let mut renames: Vec<_> = renames.into_iter().map(Some).collect();
+    let mut edited = Vec::new();
+    let mut numbers = HashSet::new();
+    for (index, line) in plan.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // some editors replace the tab by spaces
+        let (number, path) = line
+            .split_once('\t')
+            .or_else(|| line.split_once(' '))
+            .ok_or(Error::InvalidLine(line_number))?;
+        let number: usize = number
+            .trim()
+            .parse()
+            .map_err(|_| Error::InvalidLine(line_number))?;
+        if path.is_empty() {
+            return Err(Error::EmptyPath(line_number));
+        }
+        if !numbers.insert(number) {
+            return Err(Error::DuplicateNumber {
+                line: line_number,
+                number,
+            });
+        }
+        let mut rename_info = number
+            .checked_sub(1)
+            .and_then(|index| renames.get_mut(index))
+            .and_then(Option::take)
+            .ok_or(Error::UnknownNumber {
+                line: line_number,
+                number,
+            })?;
+        rename_info.new_path = PathBuf::from(path);
+        edited.push(rename_info);
+    }
+    Ok(edited)
+}
+
+/// `$VISUAL` or `$EDITOR` can have arguments like `code --wait`
+fn open_editor(file: &Path) -> Result<(), Error> {
+    let editor = EDITOR_VARS
+        .iter()
+        .find_map(|var| {
+            env::var(var)
+                .ok()
+                .filter(|editor| !editor.trim().is_empty())
+        })
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+    let mut words = editor.split_whitespace();
+    let status = Command::new(words.next().unwrap_or(DEFAULT_EDITOR))
+        .args(words)
+        .arg(file)
+        .status()
+        .map_err(|source| Error::Editor {
+            editor: editor.clone(),
+            source,
+        })?;
+    status.success().err_with(|| Error::EditorFailed(editor))
+}
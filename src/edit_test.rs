@@ -0,0 +1,84 @@
+use super::*;
+use crate::fs::FileInfo;
+
+fn renames() -> Vec<RenameInfo> {
+    ["./a_1", "./b_2", "./c_3"]
+        .iter()
+        .map(|path| RenameInfo {
+            old_file: FileInfo::file(PathBuf::from(path)),
+            new_path: PathBuf::from(path.replace('_', "-")),
+            explanation: None,
+        })
+        .collect()
+}
+
+fn new_paths(renames: &[RenameInfo]) -> Vec<(&str, &str)> {
+    renames
+        .iter()
+        .map(|rename_info| {
+            (
+                rename_info.old_file.path.to_str().unwrap(),
+                rename_info.new_path.to_str().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn create_private_plan_file() {
+    let path = create_plan_file("1\t./a\n").unwrap();
+    let content = fs::read_to_string(&path);
+    #[cfg(unix)]
+    let mode = std::os::unix::fs::PermissionsExt::mode(&fs::metadata(&path).unwrap().permissions());
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(content.unwrap(), "1\t./a\n");
+    #[cfg(unix)]
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn write_numbered_lines() {
+    assert_eq!(
+        write_plan(&renames()).unwrap(),
+        format!("{}1\t./a-1\n2\t./b-2\n3\t./c-3\n", HEADER)
+    );
+}
+
+#[test]
+fn parse_unchanged() {
+    let plan = write_plan(&renames()).unwrap();
+
+    assert_eq!(
+        new_paths(&parse_plan(&plan, renames()).unwrap()),
+        vec![("./a_1", "./a-1"), ("./b_2", "./b-2"), ("./c_3", "./c-3")]
+    );
+}
+
+#[test]
+fn parse_edited() {
+    let plan = "# comment\n3\t./c three\n\n1 ./one\n";
+
+    assert_eq!(
+        new_paths(&parse_plan(plan, renames()).unwrap()),
+        vec![("./c_3", "./c three"), ("./a_1", "./one")]
+    );
+}
+
+#[test]
+fn parse_invalid_lines() {
+    let error = |plan: &str| parse_plan(plan, renames()).unwrap_err().to_string();
+
+    assert_eq!(
+        error("1\t./a\nx\t./b\n"),
+        "Line 2 doesn't start with a number and a tab"
+    );
+    assert_eq!(
+        error("./a\n"),
+        "Line 1 doesn't start with a number and a tab"
+    );
+    assert_eq!(error("2\t\n"), "Line 1 has no path");
+    assert_eq!(error("4\t./d\n"), "Line 1 has the unknown number 4");
+    assert_eq!(error("0\t./d\n"), "Line 1 has the unknown number 0");
+    assert_eq!(error("1\t./a\n1\t./b\n"), "Line 2 repeats the number 1");
+}
@@ -0,0 +1,74 @@
+use async_std::fs::File;
+use async_std::io::{self, ReadExt};
+use async_std::path::{Path, PathBuf};
+use std::ffi::OsStr;
+
+#[cfg(test)]
+#[path = "./extension_test.rs"]
+pub mod extension_test;
+
+/// number of bytes which are read to detect the file type
+const HEADER_LEN: u64 = 8192;
+
+/// extensions which are interchangeable for the same file type
+const ALIASES: &[&[&str]] = &[
+    &["jpg", "jpeg", "jpe", "jfif"],
+    &["tif", "tiff"],
+    &["mpg", "mpeg"],
+    &["mid", "midi"],
+    &["gz", "tgz"],
+    &["ogg", "oga", "ogv", "ogx", "spx"],
+    &[
+        "zip", "jar", "war", "ear", "apk", "aar", "ipa", "xpi", "whl", "nupkg", "vsix", "cbz",
+        "kmz", "3mf",
+    ],
+];
+
+/// detects the file type by its magic bytes.
+/// Text types are ignored, as they are only guessed by the first characters
+pub async fn detect(path: &Path) -> io::Result<Option<&'static str>> {
+    let mut header = Vec::with_capacity(HEADER_LEN as usize);
+    File::open(path)
+        .await?
+        .take(HEADER_LEN)
+        .read_to_end(&mut header)
+        .await?;
+
+    Ok(infer::get(&header)
+        .filter(|file_type| file_type.matcher_type() != infer::MatcherType::Text)
+        .map(|file_type| file_type.extension()))
+}
+
+fn is_alias(extension: &str, detected: &str) -> bool {
+    extension == detected
+        || ALIASES
+            .iter()
+            .any(|aliases| aliases.contains(&extension) && aliases.contains(&detected))
+}
+
+fn is_known(extension: &str) -> bool {
+    infer::is_supported(extension) || ALIASES.iter().any(|aliases| aliases.contains(&extension))
+}
+
+/// replaces the extension of `path` with the `detected` one or appends it to a name without one.
+/// An unknown extension is kept, as container formats like `.ai` or `.pages` are detected
+/// as the type they are based on
+pub fn fix(path: &Path, detected: &str) -> PathBuf {
+    let extension = match path.extension().and_then(OsStr::to_str) {
+        Some(extension) => extension.to_lowercase(),
+        None => return append(path, detected),
+    };
+
+    if is_known(&extension) && !is_alias(&extension, detected) {
+        path.with_extension(detected)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn append(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+    path.with_file_name(file_name)
+}
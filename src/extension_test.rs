@@ -0,0 +1,70 @@
+use super::*;
+use crate::utils::TempDir;
+
+#[test]
+fn fix_wrong_extension() {
+    assert_eq!(
+        fix(Path::new("dir/photo.png"), "jpg"),
+        PathBuf::from("dir/photo.jpg")
+    );
+}
+
+#[test]
+fn fix_missing_extension() {
+    assert_eq!(
+        fix(Path::new("dir/document"), "pdf"),
+        PathBuf::from("dir/document.pdf")
+    );
+}
+
+#[test]
+fn keep_correct_extension() {
+    assert_eq!(
+        fix(Path::new("dir/photo.JPG"), "jpg"),
+        PathBuf::from("dir/photo.JPG")
+    );
+}
+
+#[test]
+fn keep_alias_extension() {
+    assert_eq!(
+        fix(Path::new("dir/photo.jpeg"), "jpg"),
+        PathBuf::from("dir/photo.jpeg")
+    );
+    assert_eq!(
+        fix(Path::new("dir/lib.jar"), "zip"),
+        PathBuf::from("dir/lib.jar")
+    );
+}
+
+#[test]
+fn keep_unknown_extension() {
+    assert_eq!(
+        fix(Path::new("dir/logo.ai"), "pdf"),
+        PathBuf::from("dir/logo.ai")
+    );
+    assert_eq!(
+        fix(Path::new("dir/doc.pages"), "zip"),
+        PathBuf::from("dir/doc.pages")
+    );
+    assert_eq!(
+        fix(Path::new("dir/report.v2"), "pdf"),
+        PathBuf::from("dir/report.v2")
+    );
+}
+
+#[async_std::test]
+async fn detect_png() {
+    let dir = TempDir::new();
+    let path = dir.file("image", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+
+    assert_matches!(detect(&PathBuf::from(path)).await, Ok(Some("png")));
+}
+
+#[async_std::test]
+async fn ignore_text() {
+    let dir = TempDir::new();
+    let path = dir.file("script", b"#!/usr/bin/env python\n");
+
+    assert_matches!(detect(&PathBuf::from(path)).await, Ok(None));
+}
@@ -1,13 +1,21 @@
 use super::cli;
+use super::edit;
+use super::prompt::Prompt;
 use super::replace;
 use super::stats::Stats;
 use crate::utils::SelectMapExt;
+use async_std::io::prelude::BufReadExt;
 use async_std::sync::RwLock;
-use async_std::{fs, io, path::PathBuf, stream};
+use async_std::{
+    fs, io,
+    path::{Path, PathBuf},
+    stream,
+};
 use bool_ext::BoolExt;
 use futures::stream::{Stream, StreamExt, TryStreamExt};
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::SystemTime;
 
 #[cfg(test)]
 #[path = "./fs_test.rs"]
@@ -22,8 +30,23 @@ pub enum Error {
     Io(#[from] io::Error),
     #[error(transparent)]
     Replace(#[from] replace::Error),
+    #[error(transparent)]
+    Edit(#[from] edit::Error),
     #[error("The parent directory `{}` does not exist", .0.to_string_lossy())]
     NonExistingParent(PathBuf),
+    #[error("The target `{}` already exists", .0.to_string_lossy())]
+    TargetExists(PathBuf),
+    #[error(
+        "`{}` and `{}` would both be renamed to `{}`",
+        .first.to_string_lossy(),
+        .second.to_string_lossy(),
+        .target.to_string_lossy()
+    )]
+    DuplicateTarget {
+        first: PathBuf,
+        second: PathBuf,
+        target: PathBuf,
+    },
 }
 
 pub async fn rename(
@@ -31,24 +54,202 @@ pub async fn rename(
     replacer: &replace::Replacer,
     stats: &Stats,
 ) -> Result<(), Error> {
-    let done_targets = Rc::new(RwLock::new(HashSet::new()));
-    read_dir(opts)
+    let done_targets = Rc::new(RwLock::new(HashMap::new()));
+    let files = read_dir(opts)
         .await?
         .filter_map(|file_entry| async { check_file_type(file_entry, opts).await })
         .try_filter(|file| {
             let done_targets = Rc::clone(&done_targets);
             let file = file.clone();
             async move { check_unique_pattern_match(&file, replacer, done_targets).await }
+        });
+    let files = if replacer.has_sequence() {
+        stream::from_iter(number_sequence(files.collect().await, opts).await).boxed_local()
+    } else {
+        files.map_ok(|file| (file, 0)).boxed_local()
+    };
+
+    // the renames are confirmed one after another
+    let prompt = opts.interactive.some_with(Prompt::stdin);
+    let prompt = prompt.as_ref();
+    let rename_infos = files
+        .map_ok(|(file, sequence_index)| async move {
+            rename_file_path(file, sequence_index, replacer).await
         })
-        .map_ok(|file| async { rename_file_path(file, replacer).await })
+        .filter_map(|rename_info| async { handle_error_to_user(rename_info, opts, stats).await });
+    let rename_infos = rename_infos
+        .and_then(|rename_info| rename_info)
         .filter_map(|rename_info| async { handle_error_to_user(rename_info, opts, stats).await })
-        .try_for_each_concurrent(None, |rename_info| {
+        .try_collect()
+        .await?;
+    if opts.edit {
+        return edit_renames(rename_infos, opts, done_targets, stats, prompt).await;
+    }
+    let rename_infos = check_duplicate_targets(rename_infos, opts, stats).await?;
+    stream::from_iter(rename_infos)
+        .map(Ok)
+        .try_for_each_concurrent(opts.interactive.some(1), |rename_info| {
             let done_targets = Rc::clone(&done_targets);
-            async { process_file_rename(rename_info.await, opts, done_targets, stats).await }
+            async move {
+                process_file_rename(Ok(rename_info), opts, done_targets, stats, prompt).await
+            }
         })
         .await
 }
 
+/// two files must not get the same target, like identical files by a content hash.
+/// The whole plan is checked before the first rename
+async fn check_duplicate_targets(
+    rename_infos: Vec<RenameInfo>,
+    opts: &cli::Cli,
+    stats: &Stats,
+) -> Result<Vec<RenameInfo>, Error> {
+    let mut targets = HashMap::new();
+    let mut checked = Vec::with_capacity(rename_infos.len());
+    for rename_info in rename_infos {
+        let result = check_duplicate_target(&rename_info, &mut targets).map(|_| rename_info);
+        if let Some(rename_info) = handle_error_to_user(result, opts, stats).await {
+            checked.push(rename_info?);
+        }
+    }
+    Ok(checked)
+}
+
+fn check_duplicate_target(
+    rename_info: &RenameInfo,
+    targets: &mut HashMap<PathBuf, PathBuf>,
+) -> Result<(), Error> {
+    let (old_path, new_path) = (&rename_info.old_file.path, &rename_info.new_path);
+    if old_path == new_path {
+        return Ok(());
+    }
+    match targets.insert(new_path.clone(), old_path.clone()) {
+        Some(first) => Err(Error::DuplicateTarget {
+            first,
+            second: old_path.clone(),
+            target: new_path.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// renames the files to the paths, which the user edited.
+/// All paths are checked before the first rename
+async fn edit_renames(
+    mut rename_infos: Vec<RenameInfo>,
+    opts: &cli::Cli,
+    done_targets: Rc<RwLock<HashMap<PathBuf, PathBuf>>>,
+    stats: &Stats,
+    prompt: Option<&Prompt>,
+) -> Result<(), Error> {
+    rename_infos
+        .sort_unstable_by(|rename_info, other| rename_info.old_file.path.cmp(&other.old_file.path));
+    let rename_infos = check_edited_paths(edit::edit(rename_infos)?, opts, stats).await?;
+    for rename_info in rename_infos {
+        process_file_rename(
+            Ok(rename_info),
+            opts,
+            Rc::clone(&done_targets),
+            stats,
+            prompt,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// unlike the rules, an edited path must not exist and its directory must.
+/// Two files must not get the same path either
+async fn check_edited_paths(
+    rename_infos: Vec<RenameInfo>,
+    opts: &cli::Cli,
+    stats: &Stats,
+) -> Result<Vec<RenameInfo>, Error> {
+    let mut targets = HashMap::new();
+    let mut checked = Vec::with_capacity(rename_infos.len());
+    for rename_info in rename_infos {
+        let result = check_edited_path(&rename_info, &mut targets)
+            .await
+            .map(|_| rename_info);
+        if let Some(rename_info) = handle_error_to_user(result, opts, stats).await {
+            checked.push(rename_info?);
+        }
+    }
+    Ok(checked)
+}
+
+async fn check_edited_path(
+    rename_info: &RenameInfo,
+    targets: &mut HashMap<PathBuf, PathBuf>,
+) -> Result<(), Error> {
+    let (old_path, new_path) = (&rename_info.old_file.path, &rename_info.new_path);
+    if old_path == new_path {
+        return Ok(());
+    }
+    check_parent(new_path).await?;
+    check_sibling_collision(old_path, new_path).await?;
+    check_duplicate_target(rename_info, targets)
+}
+
+/// applies the rules to the names or paths of stdin and prints every new one, without renaming.
+/// Names without a match are printed unchanged, so that each line of stdin gets one of stdout
+pub async fn transform(
+    opts: &cli::Cli,
+    replacer: &replace::Replacer,
+    stats: &Stats,
+) -> Result<(), Error> {
+    let mut lines = io::BufReader::new(io::stdin()).lines();
+    let mut sequence_index = 0;
+    while let Some(line) = lines.next().await {
+        let old_path = PathBuf::from(line?);
+        let new_path = match transform_name(&old_path, sequence_index, opts, replacer, stats).await
+        {
+            Ok(Some(new_path)) => {
+                sequence_index += 1;
+                new_path
+            }
+            Ok(None) => old_path,
+            Err(error) if opts.continue_on_error => {
+                stats.error(&error);
+                old_path
+            }
+            Err(error) => return Err(error),
+        };
+        println!("{}", new_path.to_string_lossy());
+    }
+    Ok(())
+}
+
+/// the new path of a name from stdin or `None` without a match.
+/// Existing paths keep their file type, the others are files
+async fn transform_name(
+    path: &Path,
+    sequence_index: u64,
+    opts: &cli::Cli,
+    replacer: &replace::Replacer,
+    stats: &Stats,
+) -> Result<Option<PathBuf>, Error> {
+    if path.as_os_str().is_empty() {
+        return Ok(None);
+    }
+    let file = FileInfo {
+        path: path.to_path_buf(),
+        file_type: fs::symlink_metadata(path)
+            .await
+            .map_or(FileType::File, |metadata| metadata.file_type().into()),
+    };
+    if !has_type(file.file_type, opts) || !replacer.is_match(&file)? {
+        return Ok(None);
+    }
+    let (new_path, explanation) = replacer.replace_file(&file, sequence_index).await?;
+    stats.explain(&RenameInfo {
+        old_file: file,
+        new_path: new_path.clone(),
+        explanation,
+    });
+    Ok(Some(new_path))
+}
+
 async fn read_dir(
     opts: &cli::Cli,
 ) -> Result<Box<dyn Stream<Item = io::Result<fs::DirEntry>> + Unpin>, io::Error> {
@@ -84,17 +285,52 @@ async fn read_dir_recursive(
     )))
 }
 
+/// The kind of a file. Names from stdin, which don't exist, are files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileType {
+    File,
+    Dir,
+    SymLink,
+}
+
+impl FileType {
+    pub fn is_file(&self) -> bool {
+        matches!(self, Self::File)
+    }
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Self::Dir)
+    }
+    pub fn is_symlink(&self) -> bool {
+        matches!(self, Self::SymLink)
+    }
+}
+
+impl From<std::fs::FileType> for FileType {
+    fn from(file_type: std::fs::FileType) -> Self {
+        if file_type.is_symlink() {
+            Self::SymLink
+        } else if file_type.is_dir() {
+            Self::Dir
+        } else {
+            Self::File
+        }
+    }
+}
+
 #[cfg(not(test))]
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub path: PathBuf,
-    pub file_type: fs::FileType,
+    pub file_type: FileType,
 }
 
 #[cfg(not(test))]
 impl FileInfo {
     fn new(path: PathBuf, file_type: fs::FileType) -> Self {
-        Self { path, file_type }
+        Self {
+            path,
+            file_type: file_type.into(),
+        }
     }
 }
 
@@ -105,18 +341,72 @@ async fn check_file_type(
     let file_entry = try_wrap_err!(file_entry);
     let file_type = try_wrap_err!(file_entry.file_type().await);
 
-    ((file_type.is_file() && opts.file)
+    has_type(file_type.into(), opts).some_with(|| Ok(FileInfo::new(file_entry.path(), file_type)))
+}
+
+/// the selected types of files are renamed
+fn has_type(file_type: FileType, opts: &cli::Cli) -> bool {
+    (file_type.is_file() && opts.file)
         || (file_type.is_dir() && opts.directory)
-        || (file_type.is_symlink() && opts.symlink))
-        .some_with(|| Ok(FileInfo::new(file_entry.path(), file_type)))
+        || (file_type.is_symlink() && opts.symlink)
 }
 
 async fn check_unique_pattern_match(
     file: &FileInfo,
     replacer: &replace::Replacer,
-    done_targets: Rc<RwLock<HashSet<PathBuf>>>,
+    done_targets: Rc<RwLock<HashMap<PathBuf, PathBuf>>>,
 ) -> bool {
-    !done_targets.read().await.contains(&file.path) && replacer.is_match(&file.path).unwrap_or(true)
+    !done_targets.read().await.contains_key(&file.path) && replacer.is_match(file).unwrap_or(true)
+}
+
+/// sorts the files by the selected order and numbers them for the sequence placeholder
+async fn number_sequence(
+    files: Vec<Result<FileInfo, Error>>,
+    opts: &cli::Cli,
+) -> Vec<Result<(FileInfo, u64), Error>> {
+    let mut numbered = Vec::with_capacity(files.len());
+    let mut sortable = Vec::with_capacity(files.len());
+    for file in files {
+        let file = match file {
+            Ok(file) => file,
+            Err(error) => {
+                numbered.push(Err(error));
+                continue;
+            }
+        };
+        let modified = match opts.sort {
+            cli::SortOrder::Name => None,
+            cli::SortOrder::Mtime => match modified(&file).await {
+                Ok(modified) => Some(modified),
+                Err(error) => {
+                    numbered.push(Err(error.into()));
+                    continue;
+                }
+            },
+        };
+        let parent = opts
+            .sequence_per_dir
+            .some_with(|| file.path.parent().map(PathBuf::from))
+            .flatten();
+        sortable.push(((parent, modified, file.path.clone()), file));
+    }
+    sortable.sort_unstable_by(|(key, _), (other_key, _)| key.cmp(other_key));
+
+    let mut index = 0;
+    let mut last_parent = None;
+    for ((parent, _, _), file) in sortable {
+        if parent != last_parent {
+            index = 0;
+            last_parent = parent;
+        }
+        numbered.push(Ok((file, index)));
+        index += 1;
+    }
+    numbered
+}
+
+async fn modified(file: &FileInfo) -> io::Result<SystemTime> {
+    fs::symlink_metadata(&file.path).await?.modified()
 }
 
 #[derive(Debug, Clone)]
@@ -124,28 +414,84 @@ async fn check_unique_pattern_match(
 pub struct RenameInfo {
     pub old_file: FileInfo,
     pub new_path: PathBuf,
+    /// the steps of the rules, which are recorded with `--explain`
+    pub explanation: Option<Vec<replace::Step>>,
 }
 
 async fn rename_file_path(
     old_file: FileInfo,
+    sequence_index: u64,
     replacer: &replace::Replacer,
 ) -> Result<RenameInfo, Error> {
-    let new_path = replacer.replace(&old_file.path)?;
-    new_path
-        .parent()
-        .expect("Couldn't get parent!")
+    let (new_path, explanation) = replacer.replace_file(&old_file, sequence_index).await?;
+    check_parent(&new_path).await?;
+    if replacer.is_normalizing() {
+        check_sibling_collision(&old_file.path, &new_path).await?;
+    }
+
+    Ok(RenameInfo {
+        old_file,
+        new_path,
+        explanation,
+    })
+}
+
+/// the directory of the new path must exist
+async fn check_parent(new_path: &Path) -> Result<(), Error> {
+    let parent = directory(new_path)?;
+    parent
         .is_dir()
         .await
-        .err_with(|| {
-            Error::NonExistingParent(
-                new_path
-                    .parent()
-                    .expect("Couldn't get parent!")
-                    .to_path_buf(),
-            )
-        })?;
+        .err_with(|| Error::NonExistingParent(parent.to_path_buf()))
+}
+
+/// the directory of a path, which is the current one for a plain name like an edited one
+fn directory(path: &Path) -> Result<&Path, Error> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| replace::Error::NoParent(path.to_path_buf()))?;
+    Ok(if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    })
+}
+
+/// a sibling with the same name in another normalization form would be overwritten.
+/// The names are compared byte-wise, as some file systems are normalization-insensitive
+pub async fn check_sibling_collision(old_path: &Path, new_path: &Path) -> Result<(), Error> {
+    if old_path == new_path || fs::symlink_metadata(new_path).await.is_err() {
+        return Ok(());
+    }
 
-    Ok(RenameInfo { old_file, new_path })
+    let new_name = new_path.file_name();
+    let mut siblings = fs::read_dir(directory(new_path)?).await?;
+    while let Some(sibling) = siblings.next().await {
+        if Some(sibling?.file_name().as_os_str()) == new_name {
+            return Err(Error::TargetExists(new_path.to_path_buf()));
+        }
+    }
+    Ok(())
+}
+
+/// remembers the source of every target, so that a second file can't be renamed to it
+async fn claim_target(
+    rename_info: &RenameInfo,
+    done_targets: &RwLock<HashMap<PathBuf, PathBuf>>,
+) -> Result<(), Error> {
+    let mut done_targets = done_targets.write().await;
+    if let Some(first) = done_targets.get(&rename_info.new_path) {
+        return Err(Error::DuplicateTarget {
+            first: first.clone(),
+            second: rename_info.old_file.path.clone(),
+            target: rename_info.new_path.clone(),
+        });
+    }
+    done_targets.insert(
+        rename_info.new_path.clone(),
+        rename_info.old_file.path.clone(),
+    );
+    Ok(())
 }
 
 async fn handle_error_to_user<T>(
@@ -166,10 +512,11 @@ async fn handle_error_to_user<T>(
 async fn process_file_rename(
     rename_info: Result<RenameInfo, Error>,
     opts: &cli::Cli,
-    done_targets: Rc<RwLock<HashSet<PathBuf>>>,
+    done_targets: Rc<RwLock<HashMap<PathBuf, PathBuf>>>,
     stats: &Stats,
+    prompt: Option<&Prompt>,
 ) -> Result<(), Error> {
-    let rename_info = match (rename_info, opts.continue_on_error) {
+    let mut rename_info = match (rename_info, opts.continue_on_error) {
         (Ok(rename_info), _) => rename_info,
         (Err(error), false) => return Err(error),
         (Err(error), true) => {
@@ -178,10 +525,32 @@ async fn process_file_rename(
         }
     };
 
-    done_targets
-        .write()
-        .await
-        .insert(rename_info.new_path.clone());
+    // the matches are explained even if the name stays the same
+    stats.explain(&rename_info);
+
+    if rename_info.old_file.path == rename_info.new_path {
+        return Ok(());
+    }
+
+    // the user can skip the rename or type another name
+    if let Some(prompt) = prompt {
+        match prompt.confirm(&rename_info).await? {
+            Some(new_path) if new_path != rename_info.old_file.path => {
+                rename_info.new_path = new_path
+            }
+            _ => return Ok(()),
+        }
+    }
+
+    // a typed name could be the target of another file
+    if let Err(error) = claim_target(&rename_info, &done_targets).await {
+        if opts.continue_on_error {
+            stats.error(&error);
+            return Ok(());
+        } else {
+            return Err(error);
+        }
+    }
 
     stats.rename(&rename_info);
 
@@ -2,25 +2,7 @@ use super::*;
 use crate::cli::cli_test::empty_cli;
 use crate::replace::replace_test::{empty_replacer, restrictive_replacer};
 use crate::stats::Stats;
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum FileType {
-    File,
-    Dir,
-    SymLink,
-}
-
-impl FileType {
-    pub fn is_file(&self) -> bool {
-        matches!(self, Self::File)
-    }
-    pub fn is_dir(&self) -> bool {
-        matches!(self, Self::Dir)
-    }
-    pub fn is_symlink(&self) -> bool {
-        matches!(self, Self::SymLink)
-    }
-}
+use crate::utils::TempDir;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FileInfo {
@@ -41,6 +23,18 @@ impl FileInfo {
             file_type: FileType::File,
         }
     }
+    pub fn dir(path: PathBuf) -> Self {
+        Self {
+            path,
+            file_type: FileType::Dir,
+        }
+    }
+    pub fn symlink(path: PathBuf) -> Self {
+        Self {
+            path,
+            file_type: FileType::SymLink,
+        }
+    }
 }
 
 #[async_std::test]
@@ -48,7 +42,7 @@ async fn check_io_error() {
     let cli = empty_cli();
 
     assert_matches!(
-        check_file_type(Err(io::Error::new(io::ErrorKind::Other, "test")), &cli).await,
+        check_file_type(Err(io::Error::other("test")), &cli).await,
         Some(Err(Error::Io(_)))
     );
 }
@@ -56,10 +50,10 @@ async fn check_io_error() {
 #[async_std::test]
 async fn already_done_matching_target() {
     let done_path = PathBuf::from("/done");
-    let done_targets = Rc::new(RwLock::new(hashset![
-        done_path.clone(),
-        PathBuf::from("done-2")
-    ]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {
+        done_path.clone() => PathBuf::from("/old"),
+        PathBuf::from("done-2") => PathBuf::from("old-2"),
+    }));
 
     assert!(
         !check_unique_pattern_match(&FileInfo::file(done_path), &empty_replacer(), done_targets)
@@ -69,10 +63,10 @@ async fn already_done_matching_target() {
 
 #[async_std::test]
 async fn not_done_matching_target() {
-    let done_targets = Rc::new(RwLock::new(hashset![
-        PathBuf::from("/done"),
-        PathBuf::from("done-2")
-    ]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {
+        PathBuf::from("/done") => PathBuf::from("/old"),
+        PathBuf::from("done-2") => PathBuf::from("old-2"),
+    }));
 
     assert!(
         check_unique_pattern_match(
@@ -87,10 +81,10 @@ async fn not_done_matching_target() {
 #[async_std::test]
 async fn already_done_not_matching_target() {
     let done_path = PathBuf::from("/done");
-    let done_targets = Rc::new(RwLock::new(hashset![
-        done_path.clone(),
-        PathBuf::from("done-2")
-    ]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {
+        done_path.clone() => PathBuf::from("/old"),
+        PathBuf::from("done-2") => PathBuf::from("old-2"),
+    }));
 
     assert!(
         !check_unique_pattern_match(
@@ -104,10 +98,10 @@ async fn already_done_not_matching_target() {
 
 #[async_std::test]
 async fn not_done_not_matching_target() {
-    let done_targets = Rc::new(RwLock::new(hashset![
-        PathBuf::from("/done"),
-        PathBuf::from("done-2")
-    ]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {
+        PathBuf::from("/done") => PathBuf::from("/old"),
+        PathBuf::from("done-2") => PathBuf::from("old-2"),
+    }));
 
     assert!(
         !check_unique_pattern_match(
@@ -121,10 +115,10 @@ async fn not_done_not_matching_target() {
 
 #[async_std::test]
 async fn pass_matching_error() {
-    let done_targets = Rc::new(RwLock::new(hashset![
-        PathBuf::from("/done"),
-        PathBuf::from("done-2")
-    ]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {
+        PathBuf::from("/done") => PathBuf::from("/old"),
+        PathBuf::from("done-2") => PathBuf::from("old-2"),
+    }));
 
     assert!(
         check_unique_pattern_match(
@@ -139,7 +133,12 @@ async fn pass_matching_error() {
 #[async_std::test]
 async fn rename_invalid_filename() {
     assert_matches!(
-        rename_file_path(FileInfo::file(PathBuf::from(".")), &restrictive_replacer()).await,
+        rename_file_path(
+            FileInfo::file(PathBuf::from(".")),
+            0,
+            &restrictive_replacer()
+        )
+        .await,
         Err(Error::Replace(replace::Error::InvalidFileName(_)))
     );
 }
@@ -149,6 +148,7 @@ async fn rename_without_parent() {
     assert_matches!(
         rename_file_path(
             FileInfo::file(PathBuf::from("non_existant/_old")),
+            0,
             &restrictive_replacer()
         )
         .await,
@@ -162,10 +162,216 @@ async fn simple_rename() {
     let new_path = PathBuf::from("./old");
 
     assert_eq!(
-        rename_file_path(old_file.clone(), &restrictive_replacer())
+        rename_file_path(old_file.clone(), 0, &restrictive_replacer())
+            .await
+            .unwrap(),
+        RenameInfo {
+            old_file,
+            new_path,
+            explanation: None,
+        }
+    );
+}
+
+#[async_std::test]
+async fn transform_names() {
+    let mut cli = empty_cli();
+    cli.file = true;
+    let transform = |path: &str| {
+        let cli = &cli;
+        let path = PathBuf::from(path);
+        async move {
+            transform_name(&path, 0, cli, &restrictive_replacer(), &Stats::new())
+                .await
+                .unwrap()
+        }
+    };
+
+    assert_eq!(transform("_old").await, Some(PathBuf::from("old")));
+    assert_eq!(
+        transform("missing/_old").await,
+        Some(PathBuf::from("missing/old"))
+    );
+    assert_eq!(transform("unchanged").await, None);
+    assert_eq!(transform("").await, None);
+}
+
+#[async_std::test]
+async fn transform_names_of_type() {
+    let mut cli = empty_cli();
+    cli.directory = true;
+
+    assert_eq!(
+        transform_name(
+            Path::new("_old"),
+            0,
+            &cli,
+            &restrictive_replacer(),
+            &Stats::new()
+        )
+        .await
+        .unwrap(),
+        None
+    );
+}
+
+fn edited(old_path: impl Into<PathBuf>, new_path: impl Into<PathBuf>) -> RenameInfo {
+    RenameInfo {
+        old_file: FileInfo::file(old_path.into()),
+        new_path: new_path.into(),
+        explanation: None,
+    }
+}
+
+#[async_std::test]
+async fn check_edited() {
+    let dir = TempDir::new();
+    let old_path = PathBuf::from(dir.file("a", ""));
+    dir.file("keep", "");
+    let check = |rename_info: RenameInfo| async {
+        check_edited_paths(vec![rename_info], &empty_cli(), &Stats::new()).await
+    };
+
+    assert_matches!(
+        check(edited(old_path.clone(), dir.join("b")))
+            .await
+            .as_deref(),
+        Ok([_])
+    );
+    assert_matches!(
+        check(edited(old_path.clone(), old_path.clone()))
+            .await
+            .as_deref(),
+        Ok([_])
+    );
+    assert_matches!(
+        check(edited(old_path.clone(), dir.join("typo/b"))).await,
+        Err(Error::NonExistingParent(_))
+    );
+    assert_matches!(
+        check(edited(old_path, dir.join("keep"))).await,
+        Err(Error::TargetExists(_))
+    );
+}
+
+#[async_std::test]
+async fn check_edited_before_renaming() {
+    let dir = TempDir::new();
+    let renames = vec![
+        edited(dir.file("a", ""), dir.join("c")),
+        edited(dir.file("b", ""), dir.join("c")),
+        edited(dir.file("d", ""), dir.join("typo/d")),
+    ];
+
+    assert_matches!(
+        check_edited_paths(renames.clone(), &empty_cli(), &Stats::new()).await,
+        Err(Error::DuplicateTarget { .. })
+    );
+
+    let mut cli = empty_cli();
+    cli.continue_on_error = true;
+    assert_eq!(
+        check_edited_paths(renames.clone(), &cli, &Stats::new())
             .await
             .unwrap(),
-        RenameInfo { old_file, new_path }
+        renames[..1]
+    );
+}
+
+fn numbered_paths(numbered: Vec<Result<(FileInfo, u64), Error>>) -> Vec<(PathBuf, u64)> {
+    numbered
+        .into_iter()
+        .map(|numbered| {
+            let (file, index) = numbered.unwrap();
+            (file.path, index)
+        })
+        .collect()
+}
+
+#[async_std::test]
+async fn number_sequence_by_name() {
+    let cli = empty_cli();
+    let files = vec![
+        Ok(FileInfo::file(PathBuf::from("b/2"))),
+        Ok(FileInfo::file(PathBuf::from("a/2"))),
+        Ok(FileInfo::file(PathBuf::from("b/1"))),
+    ];
+
+    assert_eq!(
+        numbered_paths(number_sequence(files, &cli).await),
+        vec![
+            (PathBuf::from("a/2"), 0),
+            (PathBuf::from("b/1"), 1),
+            (PathBuf::from("b/2"), 2)
+        ]
+    );
+}
+
+#[async_std::test]
+async fn number_sequence_per_dir() {
+    let mut cli = empty_cli();
+    cli.sequence_per_dir = true;
+    let files = vec![
+        Ok(FileInfo::file(PathBuf::from("b/2"))),
+        Ok(FileInfo::file(PathBuf::from("a/2"))),
+        Ok(FileInfo::file(PathBuf::from("b/1"))),
+        Ok(FileInfo::file(PathBuf::from("a/1"))),
+    ];
+
+    assert_eq!(
+        numbered_paths(number_sequence(files, &cli).await),
+        vec![
+            (PathBuf::from("a/1"), 0),
+            (PathBuf::from("a/2"), 1),
+            (PathBuf::from("b/1"), 0),
+            (PathBuf::from("b/2"), 1)
+        ]
+    );
+}
+
+#[async_std::test]
+async fn number_sequence_passes_errors() {
+    let cli = empty_cli();
+    let files = vec![
+        Ok(FileInfo::file(PathBuf::from("a"))),
+        Err(Error::NonExistingParent(PathBuf::from("b"))),
+    ];
+
+    assert_matches!(
+        number_sequence(files, &cli).await.as_slice(),
+        [Err(Error::NonExistingParent(_)), Ok(_)]
+    );
+}
+
+fn normalization_dir() -> TempDir {
+    let dir = TempDir::new();
+    dir.file("cafe\u{301}", "");
+    dir
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn sibling_collision_in_other_form() {
+    let dir = normalization_dir();
+    let target = PathBuf::from(dir.file("caf\u{e9}", ""));
+
+    assert_matches!(
+        check_sibling_collision(&PathBuf::from(dir.join("cafe\u{301}")), &target).await,
+        Err(Error::TargetExists(_))
+    );
+}
+
+#[async_std::test]
+async fn no_sibling_collision() {
+    let dir = normalization_dir();
+
+    assert_matches!(
+        check_sibling_collision(
+            &PathBuf::from(dir.join("cafe\u{301}")),
+            &PathBuf::from(dir.join("caf\u{e9}"))
+        )
+        .await,
+        Ok(())
     );
 }
 
@@ -217,45 +423,119 @@ async fn continue_handle_error_on_error() {
 
 #[async_std::test]
 async fn stop_on_error() {
-    let done_targets = Rc::new(RwLock::new(hashset![]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {}));
     let cli = empty_cli();
     let files_result = Err(Error::NonExistingParent(PathBuf::from("./old")));
 
     assert_matches!(
-        process_file_rename(files_result, &cli, done_targets, &Stats::new()).await,
+        process_file_rename(files_result, &cli, done_targets, &Stats::new(), None).await,
         Err(Error::NonExistingParent(_))
     );
 }
 
 #[async_std::test]
 async fn continue_on_error() {
-    let done_targets = Rc::new(RwLock::new(hashset![]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {}));
     let mut cli = empty_cli();
     cli.continue_on_error = true;
     let files_result = Err(Error::NonExistingParent(PathBuf::from("./old")));
 
     assert_matches!(
-        process_file_rename(files_result, &cli, done_targets, &Stats::new()).await,
+        process_file_rename(files_result, &cli, done_targets, &Stats::new(), None).await,
         Ok(())
     );
 }
 
+#[async_std::test]
+async fn skip_unchanged() {
+    let done_targets = Rc::new(RwLock::new(hashmap! {}));
+    let cli = empty_cli();
+    let path = PathBuf::from("./same");
+    let files_result = Ok(RenameInfo {
+        old_file: FileInfo::file(path.clone()),
+        new_path: path.clone(),
+        explanation: None,
+    });
+
+    {
+        let done_targets = Rc::clone(&done_targets);
+        assert_matches!(
+            process_file_rename(files_result, &cli, done_targets, &Stats::new(), None).await,
+            Ok(())
+        );
+    }
+    assert!(!done_targets.read().await.contains_key(&path));
+}
+
 #[async_std::test]
 async fn add_to_done() {
-    let done_targets = Rc::new(RwLock::new(hashset![]));
+    let done_targets = Rc::new(RwLock::new(hashmap! {}));
     let cli = empty_cli();
     let new_path = PathBuf::from("./new");
     let files_result = Ok(RenameInfo {
         old_file: FileInfo::file(PathBuf::from("./old")),
         new_path: new_path.clone(),
+        explanation: None,
     });
 
     {
-        let done_targets = done_targets.clone();
+        let done_targets = Rc::clone(&done_targets);
         assert_matches!(
-            process_file_rename(files_result, &cli, done_targets, &Stats::new()).await,
+            process_file_rename(files_result, &cli, done_targets, &Stats::new(), None).await,
             Ok(())
         );
     }
-    assert!(done_targets.read().await.contains(&new_path));
+    assert!(done_targets.read().await.contains_key(&new_path));
+}
+
+#[async_std::test]
+async fn duplicate_target() {
+    let done_targets = Rc::new(RwLock::new(hashmap! {
+        PathBuf::from("./new") => PathBuf::from("./first"),
+    }));
+    let cli = empty_cli();
+    let files_result = Ok(RenameInfo {
+        old_file: FileInfo::file(PathBuf::from("./second")),
+        new_path: PathBuf::from("./new"),
+        explanation: None,
+    });
+
+    assert_matches!(
+        process_file_rename(files_result, &cli, done_targets, &Stats::new(), None).await,
+        Err(Error::DuplicateTarget { .. })
+    );
+}
+
+#[async_std::test]
+async fn duplicate_targets_before_renaming() {
+    let dir = TempDir::new();
+    for (name, content) in [("a.txt", "same"), ("b.txt", "same"), ("c.txt", "other")] {
+        dir.file(name, content);
+    }
+    let mut cli = empty_cli();
+    cli.run = true;
+    cli.file = true;
+    cli.search_pattern = Some(r".+\.txt".to_string());
+    cli.replace_pattern = Some("${hash:8}.txt".to_string());
+    cli.base_path = PathBuf::from(dir.path());
+    let replacer = replace::Replacer::new(&cli).unwrap();
+    let names = || {
+        let mut names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    };
+
+    assert_matches!(
+        rename(&cli, &replacer, &Stats::new()).await,
+        Err(Error::DuplicateTarget { .. })
+    );
+    assert_eq!(names(), ["a.txt", "b.txt", "c.txt"]);
+
+    cli.continue_on_error = true;
+    assert_matches!(rename(&cli, &replacer, &Stats::new()).await, Ok(()));
+    assert_eq!(names().len(), 3);
+    assert!(!names().contains(&"c.txt".to_string()));
 }
@@ -0,0 +1,69 @@
+use async_std::fs::File;
+use async_std::io::{self, ReadExt};
+use async_std::path::Path;
+use sha2::Digest;
+use std::str::FromStr;
+
+#[cfg(test)]
+#[path = "./hash_test.rs"]
+pub mod hash_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Unknown hash algorithm `{0}`")]
+    UnknownAlgorithm(String),
+}
+
+/// size of the chunks in which the content is read
+const BUFFER_LEN: usize = 64 * 1024;
+
+/// Algorithm of the content hash placeholder like `${hash:sha256:12}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Sha256,
+    Sha1,
+    Md5,
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(algorithm: &str) -> Result<Self, Self::Err> {
+        match algorithm.to_lowercase().as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "sha1" => Ok(Self::Sha1),
+            "md5" => Ok(Self::Md5),
+            _ => Err(Error::UnknownAlgorithm(algorithm.to_string())),
+        }
+    }
+}
+
+impl Algorithm {
+    /// hashes the content of the file as lowercase hex digits
+    pub async fn digest(self, path: &Path) -> io::Result<String> {
+        match self {
+            Self::Sha256 => digest::<sha2::Sha256>(path).await,
+            Self::Sha1 => digest::<sha1::Sha1>(path).await,
+            Self::Md5 => digest::<md5::Md5>(path).await,
+        }
+    }
+}
+
+/// the file is streamed in chunks, so that big files don't have to fit into memory
+async fn digest<D: Digest>(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0; BUFFER_LEN];
+    loop {
+        let len = file.read(&mut buffer).await?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
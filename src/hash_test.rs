@@ -0,0 +1,43 @@
+use super::*;
+use crate::utils::TempDir;
+
+#[test]
+fn parse_algorithm() {
+    assert_matches!("sha256".parse(), Ok(Algorithm::Sha256));
+    assert_matches!("SHA1".parse(), Ok(Algorithm::Sha1));
+    assert_matches!("md5".parse(), Ok(Algorithm::Md5));
+    assert_matches!(
+        "crc32".parse::<Algorithm>(),
+        Err(Error::UnknownAlgorithm(_))
+    );
+}
+
+#[async_std::test]
+async fn digest_content() {
+    let dir = TempDir::new();
+    let path = dir.file("content", "abc");
+    let path = Path::new(path.as_os_str());
+
+    assert_eq!(
+        Algorithm::Sha256.digest(path).await.unwrap(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        Algorithm::Sha1.digest(path).await.unwrap(),
+        "a9993e364706816aba3e25717850c26c9cd0d89d"
+    );
+    assert_eq!(
+        Algorithm::Md5.digest(path).await.unwrap(),
+        "900150983cd24fb0d6963f7d28e17f72"
+    );
+}
+
+#[async_std::test]
+async fn digest_missing_file() {
+    assert_matches!(
+        Algorithm::Sha256
+            .digest(Path::new("non_existant/foo"))
+            .await,
+        Err(_)
+    );
+}
@@ -14,10 +14,24 @@ extern crate maplit;
 #[macro_use]
 pub mod utils;
 
+pub mod case;
 pub mod cli;
+pub mod config;
+pub mod edit;
+pub mod extension;
 pub mod fs;
+pub mod hash;
+pub mod matcher;
+pub mod media;
+pub mod normalize;
+pub mod prompt;
+pub mod random;
 pub mod replace;
+pub mod rules;
+pub mod slug;
 pub mod stats;
+pub mod template;
+pub mod wildcard;
 
 pub use cli::Cli;
 pub use replace::Replacer;
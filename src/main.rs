@@ -11,8 +11,12 @@ use structopt::StructOpt;
 async fn main() {
     let mut stats = Stats::new();
 
-    let mut cli_opts = Cli::from_args();
-    if let Err(error) = cli_opts.post_automations() {
+    let matches = Cli::clap().get_matches();
+    let mut cli_opts = Cli::from_clap(&matches);
+    if let Err(error) = cli_opts
+        .load_config(&matches)
+        .and_then(|_| cli_opts.post_automations())
+    {
         stats.error(&error);
         return;
     }
@@ -22,12 +26,20 @@ async fn main() {
     let replacer = match Replacer::new(&cli_opts) {
         Ok(replacer) => replacer,
         Err(error) => {
-            stats.error(&error);
+            stats.diagnostic(&error);
             return;
         }
     };
+    for warning in replacer.warnings() {
+        stats.diagnostic(&warning);
+    }
 
-    if let Err(error) = fs::rename(&cli_opts, &replacer, &stats).await {
+    let result = if cli_opts.stdin_names {
+        fs::transform(&cli_opts, &replacer, &stats).await
+    } else {
+        fs::rename(&cli_opts, &replacer, &stats).await
+    };
+    if let Err(error) = result {
         stats.error(&error);
         return;
     }
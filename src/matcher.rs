@@ -0,0 +1,341 @@
+use miette::{Diagnostic, SourceSpan};
+use regex_syntax::ast;
+use regex_syntax::hir;
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+
+#[cfg(test)]
+#[path = "./matcher_test.rs"]
+pub mod matcher_test;
+
+#[derive(thiserror::Error, Debug, Diagnostic)]
+pub enum Error {
+    #[error(transparent)]
+    Regex(regex::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Syntax(SyntaxError),
+    #[error("The `--fancy` flag needs frs to be built with the `fancy` feature")]
+    FancyUnsupported,
+    #[cfg(feature = "fancy")]
+    #[error(transparent)]
+    Fancy(#[from] fancy_regex::Error),
+}
+
+impl Error {
+    /// locates the syntax error in the pattern, which `regex` reports as text only
+    fn new(pattern: &str, error: regex::Error) -> Self {
+        let (span, kind) = match regex_syntax::Parser::new().parse(pattern) {
+            Err(regex_syntax::Error::Parse(error)) => {
+                (*error.span(), Kind::Ast(error.kind().clone()))
+            }
+            Err(regex_syntax::Error::Translate(error)) => {
+                (*error.span(), Kind::Hir(error.kind().clone()))
+            }
+            // e.g. the size limit is exceeded
+            _ => return Self::Regex(error),
+        };
+        Self::Syntax(SyntaxError {
+            pattern: pattern.to_string(),
+            span: (span.start.offset, span.end.offset - span.start.offset).into(),
+            message: kind.to_string(),
+            hint: kind.hint(),
+        })
+    }
+}
+
+/// A syntax error of a search pattern, which is shown with the failing position
+#[derive(thiserror::Error, Debug, Diagnostic)]
+#[error("The search pattern is invalid")]
+pub struct SyntaxError {
+    #[source_code]
+    pattern: String,
+    #[label("{message}")]
+    span: SourceSpan,
+    message: String,
+    #[help]
+    pub hint: Option<Hint>,
+}
+
+/// A solution for a common mistake in a search pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hint {
+    /// lookaround and backreferences
+    Fancy,
+    /// a leading `*` like in `*.jpg`
+    Glob,
+    /// brackets or parentheses of a name
+    FixedStrings,
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fancy => "Lookaround and backreferences are supported with `--fancy`",
+            Self::Glob => "This looks like a glob. Use `--glob` or write `.*` for any characters",
+            Self::FixedStrings => {
+                "Escape brackets and parentheses with `\\` or search for the plain text with `-F`"
+            }
+        })
+    }
+}
+
+enum Kind {
+    Ast(ast::ErrorKind),
+    Hir(hir::ErrorKind),
+}
+
+impl Kind {
+    fn hint(&self) -> Option<Hint> {
+        match self {
+            Self::Ast(ast::ErrorKind::UnsupportedLookAround)
+            | Self::Ast(ast::ErrorKind::UnsupportedBackreference) => Some(Hint::Fancy),
+            Self::Ast(ast::ErrorKind::RepetitionMissing) => Some(Hint::Glob),
+            Self::Ast(ast::ErrorKind::ClassUnclosed)
+            | Self::Ast(ast::ErrorKind::GroupUnclosed)
+            | Self::Ast(ast::ErrorKind::GroupUnopened) => Some(Hint::FixedStrings),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ast(kind) => kind.fmt(f),
+            Self::Hir(kind) => kind.fmt(f),
+        }
+    }
+}
+
+/// How the search patterns are compiled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Syntax {
+    pub case_insensitive: bool,
+    /// the backtracking engine of `fancy-regex` instead of `regex`
+    pub fancy: bool,
+}
+
+/// The matches of a name, which are replaced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selection {
+    All,
+    /// the first matches up to this count
+    First(usize),
+    /// the match at this position, starting at 1
+    Nth(usize),
+    Last,
+}
+
+impl Selection {
+    /// `position` starts at 1 and `count` is the number of all matches
+    fn includes(self, position: usize, count: usize) -> bool {
+        match self {
+            Self::All => true,
+            Self::First(limit) => position <= limit,
+            Self::Nth(nth) => position == nth,
+            Self::Last => position == count,
+        }
+    }
+}
+
+/// The capture groups of a match
+pub trait Groups {
+    fn get(&self, index: usize) -> Option<&str>;
+    fn name(&self, name: &str) -> Option<&str>;
+}
+
+/// A compiled search pattern of a regex engine
+pub trait Matcher: fmt::Debug {
+    fn is_match(&self, text: &str) -> Result<bool, Error>;
+
+    /// calls `f` with the range and groups of every non-overlapping match in order
+    fn for_each_match(
+        &self,
+        text: &str,
+        f: &mut dyn FnMut(Range<usize>, &dyn Groups),
+    ) -> Result<(), Error>;
+
+    /// replaces the selected matches by the result of `replacer` for their range and groups.
+    /// Without a replaced match the text is borrowed
+    fn replace<'t>(
+        &self,
+        text: &'t str,
+        selection: Selection,
+        replacer: &mut dyn FnMut(Range<usize>, &dyn Groups) -> String,
+    ) -> Result<Cow<'t, str>, Error> {
+        let mut count = 0;
+        if selection == Selection::Last {
+            self.for_each_match(text, &mut |_, _| count += 1)?;
+        }
+        let mut replaced = String::new();
+        let mut last_end = 0;
+        let mut position = 0;
+        let mut is_replaced = false;
+        self.for_each_match(text, &mut |range, groups| {
+            position += 1;
+            if selection.includes(position, count) {
+                replaced.push_str(&text[last_end..range.start]);
+                replaced.push_str(&replacer(range.clone(), groups));
+                last_end = range.end;
+                is_replaced = true;
+            }
+        })?;
+        if !is_replaced {
+            return Ok(Cow::Borrowed(text));
+        }
+        replaced.push_str(&text[last_end..]);
+        Ok(Cow::Owned(replaced))
+    }
+
+    /// the names of the groups in their order, where the unnamed ones are `None`
+    fn capture_names(&self) -> Vec<Option<&str>>;
+
+    fn as_str(&self) -> &str;
+}
+
+/// the position of the first `.` outside of a class, which is followed by a literal like in `photo.jpg`.
+/// It matches any character instead of only a dot
+pub fn unescaped_dot(pattern: &str) -> Option<usize> {
+    let mut is_escaped = false;
+    let mut class_depth = 0_usize;
+    let mut chars = pattern.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            _ if is_escaped => is_escaped = false,
+            '\\' => is_escaped = true,
+            '[' => class_depth += 1,
+            ']' => class_depth = class_depth.saturating_sub(1),
+            '.' if class_depth == 0 => {
+                if matches!(chars.peek(), Some((_, next)) if next.is_alphanumeric()) {
+                    return Some(index);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// a capture group holds more than an alternation like `(IMG|DSC)`, so its value is likely needed.
+/// Patterns, which `regex` can't parse, have none
+pub fn has_value_groups(pattern: &str) -> bool {
+    fn visit(ast: &ast::Ast) -> bool {
+        match ast {
+            ast::Ast::Group(group) => {
+                let is_capture = !matches!(group.kind, ast::GroupKind::NonCapturing(_));
+                (is_capture && !matches!(*group.ast, ast::Ast::Alternation(_))) || visit(&group.ast)
+            }
+            ast::Ast::Repetition(repetition) => visit(&repetition.ast),
+            ast::Ast::Alternation(alternation) => alternation.asts.iter().any(visit),
+            ast::Ast::Concat(concat) => concat.asts.iter().any(visit),
+            _ => false,
+        }
+    }
+    ast::parse::Parser::new()
+        .parse(pattern)
+        .is_ok_and(|ast| visit(&ast))
+}
+
+pub fn compile(pattern: &str, syntax: Syntax) -> Result<Box<dyn Matcher>, Error> {
+    if syntax.fancy {
+        return compile_fancy(pattern, syntax);
+    }
+    Ok(Box::new(
+        regex::RegexBuilder::new(pattern)
+            .case_insensitive(syntax.case_insensitive)
+            .build()
+            .map_err(|error| Error::new(pattern, error))?,
+    ))
+}
+
+#[cfg(feature = "fancy")]
+fn compile_fancy(pattern: &str, syntax: Syntax) -> Result<Box<dyn Matcher>, Error> {
+    let pattern = if syntax.case_insensitive {
+        Cow::Owned(format!("(?i){}", pattern))
+    } else {
+        Cow::Borrowed(pattern)
+    };
+    Ok(Box::new(fancy_regex::Regex::new(&pattern)?))
+}
+
+#[cfg(not(feature = "fancy"))]
+fn compile_fancy(_pattern: &str, _syntax: Syntax) -> Result<Box<dyn Matcher>, Error> {
+    Err(Error::FancyUnsupported)
+}
+
+impl Groups for regex::Captures<'_> {
+    fn get(&self, index: usize) -> Option<&str> {
+        self.get(index).map(|group| group.as_str())
+    }
+
+    fn name(&self, name: &str) -> Option<&str> {
+        self.name(name).map(|group| group.as_str())
+    }
+}
+
+impl Matcher for regex::Regex {
+    fn is_match(&self, text: &str) -> Result<bool, Error> {
+        Ok(self.is_match(text))
+    }
+
+    fn for_each_match(
+        &self,
+        text: &str,
+        f: &mut dyn FnMut(Range<usize>, &dyn Groups),
+    ) -> Result<(), Error> {
+        for captures in self.captures_iter(text) {
+            f(captures.get(0).unwrap().range(), &captures);
+        }
+        Ok(())
+    }
+
+    fn capture_names(&self) -> Vec<Option<&str>> {
+        self.capture_names().collect()
+    }
+
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "fancy")]
+impl Groups for fancy_regex::Captures<'_> {
+    fn get(&self, index: usize) -> Option<&str> {
+        self.get(index).map(|group| group.as_str())
+    }
+
+    fn name(&self, name: &str) -> Option<&str> {
+        self.name(name).map(|group| group.as_str())
+    }
+}
+
+#[cfg(feature = "fancy")]
+impl Matcher for fancy_regex::Regex {
+    fn is_match(&self, text: &str) -> Result<bool, Error> {
+        Ok(self.is_match(text)?)
+    }
+
+    /// the matching can fail at runtime, e.g. by exceeding the backtrack limit
+    fn for_each_match(
+        &self,
+        text: &str,
+        f: &mut dyn FnMut(Range<usize>, &dyn Groups),
+    ) -> Result<(), Error> {
+        for captures in self.captures_iter(text) {
+            let captures = captures?;
+            let whole = captures.get(0).unwrap();
+            f(whole.start()..whole.end(), &captures);
+        }
+        Ok(())
+    }
+
+    fn capture_names(&self) -> Vec<Option<&str>> {
+        self.capture_names().collect()
+    }
+
+    fn as_str(&self) -> &str {
+        self.as_str()
+    }
+}
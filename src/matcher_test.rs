@@ -0,0 +1,144 @@
+use super::*;
+
+fn replace(pattern: &str, syntax: Syntax, text: &str) -> String {
+    replace_selected(pattern, syntax, Selection::All, text)
+}
+
+fn replace_selected(pattern: &str, syntax: Syntax, selection: Selection, text: &str) -> String {
+    compile(pattern, syntax)
+        .unwrap()
+        .replace(text, selection, &mut |_, groups| {
+            format!("<{}>", groups.get(1).unwrap_or_default())
+        })
+        .unwrap()
+        .into_owned()
+}
+
+#[test]
+fn replace_with_regex() {
+    let syntax = Syntax::default();
+
+    assert_eq!(replace("(b+)", syntax, "abba_b"), "a<bb>a_<b>");
+    assert_eq!(replace("(x)", syntax, "ab"), "ab");
+    assert_matches!(
+        compile("(x)", syntax)
+            .unwrap()
+            .replace("ab", Selection::All, &mut |_, _| String::new()),
+        Ok(Cow::Borrowed("ab"))
+    );
+}
+
+#[test]
+fn replace_selected_matches() {
+    let syntax = Syntax::default();
+    let replace = |selection| replace_selected("(\\.)", syntax, selection, "a.b.c.d");
+
+    assert_eq!(replace(Selection::First(2)), "a<.>b<.>c.d");
+    assert_eq!(replace(Selection::Nth(2)), "a.b<.>c.d");
+    assert_eq!(replace(Selection::Last), "a.b.c<.>d");
+    assert_matches!(
+        compile("x", syntax)
+            .unwrap()
+            .replace("a.b", Selection::Nth(3), &mut |_, _| String::new()),
+        Ok(Cow::Borrowed("a.b"))
+    );
+}
+
+#[test]
+fn replace_empty_match() {
+    assert_matches!(
+        compile("^", Syntax::default())
+            .unwrap()
+            .replace("ab", Selection::All, &mut |_, _| String::new()),
+        Ok(Cow::Owned(_))
+    );
+}
+
+#[test]
+fn case_insensitive_regex() {
+    let syntax = Syntax {
+        case_insensitive: true,
+        ..Default::default()
+    };
+
+    assert_matches!(compile("abc", syntax).unwrap().is_match("ABC"), Ok(true));
+}
+
+#[test]
+fn capture_names_of_regex() {
+    assert_eq!(
+        compile("(a)(?P<x>b)", Syntax::default())
+            .unwrap()
+            .capture_names(),
+        vec![None, None, Some("x")]
+    );
+}
+
+fn hint(pattern: &str) -> Option<Hint> {
+    match compile(pattern, Syntax::default()) {
+        Err(Error::Syntax(error)) => error.hint,
+        result => panic!("no syntax error: {:?}", result),
+    }
+}
+
+#[test]
+fn hint_at_common_mistakes() {
+    assert_eq!(hint("_(?!\\d)"), Some(Hint::Fancy));
+    assert_eq!(hint("(a)\\1"), Some(Hint::Fancy));
+    assert_eq!(hint("*.jpg"), Some(Hint::Glob));
+    assert_eq!(hint("photo[1.jpg"), Some(Hint::FixedStrings));
+    assert_eq!(hint("photo (1"), Some(Hint::FixedStrings));
+    assert_eq!(hint("a{"), None);
+}
+
+#[test]
+fn locate_syntax_error() {
+    assert_matches!(
+        compile("ab(?=c)", Syntax::default()),
+        Err(Error::Syntax(SyntaxError { span, .. })) if span == (2, 3).into()
+    );
+}
+
+#[test]
+fn find_unescaped_dot() {
+    assert_eq!(unescaped_dot("IMG.jpg"), Some(3));
+    assert_eq!(unescaped_dot("IMG\\.jpg"), None);
+    assert_eq!(unescaped_dot("(.+)_(.*)"), None);
+    assert_eq!(unescaped_dot("[.]jpg"), None);
+    assert_eq!(unescaped_dot("\\\\.jpg"), Some(2));
+}
+
+#[test]
+fn find_value_groups() {
+    assert!(has_value_groups("(.+)_(.*)"));
+    assert!(has_value_groups("^(IMG|DSC)_(\\d+)"));
+    assert!(has_value_groups("(?:a(b))+"));
+    assert!(!has_value_groups("^(IMG|DSC)_"));
+    assert!(!has_value_groups("(?:IMG|DSC)_"));
+    assert!(!has_value_groups("a|b"));
+    assert!(!has_value_groups("(a"));
+}
+
+#[cfg(feature = "fancy")]
+#[test]
+fn replace_with_fancy() {
+    let syntax = Syntax {
+        case_insensitive: true,
+        fancy: true,
+    };
+
+    assert_eq!(replace("(_)(?!\\d)", syntax, "a_1_b"), "a_1<_>b");
+    assert_eq!(replace("(A)\\1", syntax, "xaay"), "x<a>y");
+    assert_eq!(replace("(z)", syntax, "ab"), "ab");
+}
+
+#[cfg(not(feature = "fancy"))]
+#[test]
+fn fancy_without_feature() {
+    let syntax = Syntax {
+        fancy: true,
+        ..Default::default()
+    };
+
+    assert_matches!(compile("a", syntax), Err(Error::FancyUnsupported));
+}
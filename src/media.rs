@@ -0,0 +1,161 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+#[path = "./media_test.rs"]
+pub mod media_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("The file `{}` has no {kind} tag `{tag}`", .path.to_string_lossy())]
+    MissingTag {
+        kind: TagKind,
+        tag: String,
+        path: PathBuf,
+    },
+    #[error("The {0} placeholders need frs to be built with the `media` feature")]
+    Unsupported(TagKind),
+    #[error("The {kind} tags of `{}` are unreadable: {message}", .path.to_string_lossy())]
+    Unreadable {
+        kind: TagKind,
+        path: PathBuf,
+        message: String,
+    },
+}
+
+/// Kind of the embedded metadata of a placeholder like `${exif:Model}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagKind {
+    Exif,
+    Id3,
+}
+
+impl TagKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "exif" => Some(Self::Exif),
+            "id3" => Some(Self::Id3),
+            _ => None,
+        }
+    }
+
+    /// the tags can only be read, if the `media` feature is enabled
+    pub fn check_supported(self) -> Result<(), Error> {
+        if cfg!(feature = "media") {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(self))
+        }
+    }
+}
+
+impl fmt::Display for TagKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Exif => "EXIF",
+            Self::Id3 => "ID3",
+        })
+    }
+}
+
+/// reads the values of `tags` from the file.
+/// A tag, which the file doesn't contain, is `None`
+#[cfg(feature = "media")]
+pub fn read(path: &Path, kind: TagKind, tags: &[&str]) -> Result<Vec<Option<String>>, Error> {
+    let unreadable = |message: String| Error::Unreadable {
+        kind,
+        path: path.to_path_buf(),
+        message,
+    };
+    let values = match kind {
+        TagKind::Exif => read_exif(path, tags).map_err(|error| unreadable(error.to_string()))?,
+        TagKind::Id3 => read_id3(path, tags).map_err(|error| unreadable(error.to_string()))?,
+    };
+    Ok(values
+        .into_iter()
+        .map(|value| {
+            value
+                .map(|value| sanitize(&value))
+                .filter(|value| !value.is_empty())
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "media"))]
+pub fn read(_path: &Path, kind: TagKind, _tags: &[&str]) -> Result<Vec<Option<String>>, Error> {
+    Err(Error::Unsupported(kind))
+}
+
+/// the tags are named like in the EXIF standard, e.g. `DateTimeOriginal` or `Model`
+#[cfg(feature = "media")]
+fn read_exif(path: &Path, tags: &[&str]) -> Result<Vec<Option<String>>, exif::Error> {
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let exif = match exif::Reader::new().read_from_container(&mut file) {
+        Ok(exif) => exif,
+        // other file types have no EXIF tags
+        Err(exif::Error::NotFound(_) | exif::Error::InvalidFormat("Unknown image format")) => {
+            return Ok(vec![None; tags.len()])
+        }
+        Err(error) => return Err(error),
+    };
+
+    Ok(tags
+        .iter()
+        .map(|tag| {
+            exif.fields()
+                .filter(|field| field.tag.to_string() == *tag)
+                // prefer the main image over the thumbnail
+                .min_by_key(|field| field.ifd_num != exif::In::PRIMARY)
+                .map(|field| {
+                    field
+                        .display_value()
+                        .to_string()
+                        .trim_matches('"')
+                        .to_string()
+                })
+        })
+        .collect())
+}
+
+/// the tags are either common names like `artist` or frame IDs like `TPE1`
+#[cfg(feature = "media")]
+fn read_id3(path: &Path, tags: &[&str]) -> Result<Vec<Option<String>>, id3::Error> {
+    use id3::TagLike;
+
+    let id3 = match id3::no_tag_ok(id3::Tag::read_from_path(path))? {
+        Some(id3) => id3,
+        None => return Ok(vec![None; tags.len()]),
+    };
+
+    Ok(tags
+        .iter()
+        .map(|tag| match *tag {
+            "artist" => id3.artist().map(str::to_string),
+            "album_artist" => id3.album_artist().map(str::to_string),
+            "album" => id3.album().map(str::to_string),
+            "title" => id3.title().map(str::to_string),
+            "genre" => id3.genre_parsed().map(|genre| genre.into_owned()),
+            "year" => id3.year().map(|year| year.to_string()),
+            "track" => id3.track().map(|track| track.to_string()),
+            "disc" => id3.disc().map(|disc| disc.to_string()),
+            id => id3
+                .get(id)
+                .and_then(|frame| frame.content().text())
+                .map(str::to_string),
+        })
+        .collect())
+}
+
+/// a tag value must not create sub directories
+#[cfg(feature = "media")]
+fn sanitize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | '\0' => '_',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect()
+}
@@ -0,0 +1,66 @@
+use super::*;
+#[cfg(feature = "media")]
+use crate::utils::TempDir;
+
+#[test]
+fn parse_tag_kind() {
+    assert_eq!(TagKind::from_name("exif"), Some(TagKind::Exif));
+    assert_eq!(TagKind::from_name("id3"), Some(TagKind::Id3));
+    assert_eq!(TagKind::from_name("xmp"), None);
+}
+
+#[cfg(not(feature = "media"))]
+#[test]
+fn unsupported_without_feature() {
+    assert_matches!(
+        TagKind::Exif.check_supported(),
+        Err(Error::Unsupported(TagKind::Exif))
+    );
+    assert_matches!(
+        read(Path::new("foo.mp3"), TagKind::Id3, &["artist"]),
+        Err(Error::Unsupported(TagKind::Id3))
+    );
+}
+
+#[cfg(feature = "media")]
+#[test]
+fn read_id3_tags() {
+    use id3::TagLike;
+
+    let dir = TempDir::new();
+    let path = dir.file("song.mp3", "");
+    let mut tag = id3::Tag::new();
+    tag.set_artist("AC/DC");
+    tag.set_title(" Thunderstruck ");
+    tag.set_year(1990);
+    tag.write_to_path(&path, id3::Version::Id3v24).unwrap();
+
+    assert_eq!(
+        read(
+            &path,
+            TagKind::Id3,
+            &["artist", "title", "year", "album", "TIT2"]
+        )
+        .unwrap(),
+        vec![
+            Some("AC_DC".to_string()),
+            Some("Thunderstruck".to_string()),
+            Some("1990".to_string()),
+            None,
+            Some("Thunderstruck".to_string()),
+        ]
+    );
+}
+
+#[cfg(feature = "media")]
+#[test]
+fn read_without_tags() {
+    let dir = TempDir::new();
+    let path = dir.file("notes.txt", "no metadata");
+
+    assert_eq!(read(&path, TagKind::Id3, &["artist"]).unwrap(), vec![None]);
+    assert_matches!(
+        read(&path, TagKind::Exif, &["Model"]),
+        Ok(values) if values == vec![None]
+    );
+}
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+#[cfg(test)]
+#[path = "./normalize_test.rs"]
+pub mod normalize_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Unknown normalization form `{0}`")]
+    UnknownForm(String),
+}
+
+/// Unicode normalization form of the names
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Form {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl FromStr for Form {
+    type Err = Error;
+
+    fn from_str(form: &str) -> Result<Self, Self::Err> {
+        match form.to_lowercase().as_str() {
+            "nfc" => Ok(Self::Nfc),
+            "nfd" => Ok(Self::Nfd),
+            "nfkc" => Ok(Self::Nfkc),
+            "nfkd" => Ok(Self::Nfkd),
+            _ => Err(Error::UnknownForm(form.to_string())),
+        }
+    }
+}
+
+impl Form {
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Self::Nfc => text.nfc().collect(),
+            Self::Nfd => text.nfd().collect(),
+            Self::Nfkc => text.nfkc().collect(),
+            Self::Nfkd => text.nfkd().collect(),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use super::*;
+
+const NFC: &str = "caf\u{e9}";
+const NFD: &str = "cafe\u{301}";
+
+#[test]
+fn parse_form() {
+    assert_matches!("nfc".parse(), Ok(Form::Nfc));
+    assert_matches!("NFD".parse(), Ok(Form::Nfd));
+    assert_matches!("nfkc".parse(), Ok(Form::Nfkc));
+    assert_matches!("nfkd".parse(), Ok(Form::Nfkd));
+    assert_matches!("nfx".parse::<Form>(), Err(Error::UnknownForm(_)));
+}
+
+#[test]
+fn apply_form() {
+    assert_eq!(Form::Nfc.apply(NFD), NFC);
+    assert_eq!(Form::Nfd.apply(NFC), NFD);
+    assert_eq!(Form::Nfc.apply(NFC), NFC);
+}
+
+#[test]
+fn apply_compatibility_form() {
+    assert_eq!(Form::Nfkc.apply("\u{fb01}le"), "file");
+    assert_eq!(Form::Nfc.apply("\u{fb01}le"), "\u{fb01}le");
+}
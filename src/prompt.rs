@@ -0,0 +1,141 @@
+use crate::fs::{self, RenameInfo};
+use async_std::path::PathBuf;
+use colored::Colorize;
+use std::cell::{Cell, RefCell};
+use std::io::{self, BufRead};
+use std::str::FromStr;
+
+#[cfg(test)]
+#[path = "./prompt_test.rs"]
+pub mod prompt_test;
+
+/// An answer to the confirmation of a rename
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Answer {
+    Yes,
+    No,
+    /// this and all following renames
+    All,
+    /// none of the following renames
+    Quit,
+    /// the new name is typed in
+    Edit,
+}
+
+impl FromStr for Answer {
+    type Err = ();
+
+    fn from_str(answer: &str) -> Result<Self, Self::Err> {
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => Ok(Self::Yes),
+            // renaming is never the default
+            "" | "n" | "no" => Ok(Self::No),
+            "a" | "all" => Ok(Self::All),
+            "q" | "quit" => Ok(Self::Quit),
+            "e" | "edit" => Ok(Self::Edit),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Whether the next rename is confirmed by the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Ask,
+    All,
+    Quit,
+}
+
+/// Confirmation of every rename with `--interactive`.
+/// The questions are written to stderr
+pub struct Prompt {
+    input: RefCell<Box<dyn BufRead>>,
+    state: Cell<State>,
+}
+
+impl Prompt {
+    pub fn new(input: Box<dyn BufRead>) -> Self {
+        Self {
+            input: RefCell::new(input),
+            state: Cell::new(State::Ask),
+        }
+    }
+
+    pub fn stdin() -> Self {
+        Self::new(Box::new(io::BufReader::new(io::stdin())))
+    }
+
+    /// the confirmed target of the rename or `None` to skip it.
+    /// The end of the input quits
+    pub async fn confirm(&self, rename_info: &RenameInfo) -> io::Result<Option<PathBuf>> {
+        match self.state.get() {
+            State::All => return Ok(Some(rename_info.new_path.clone())),
+            State::Quit => return Ok(None),
+            State::Ask => (),
+        }
+        loop {
+            eprint!(
+                "{} => {}\nRename? [y]es, [N]o, [a]ll, [q]uit, [e]dit: ",
+                rename_info.old_file.path.to_string_lossy().red(),
+                rename_info.new_path.to_string_lossy().green(),
+            );
+            let line = match self.read_line()? {
+                Some(line) => line,
+                None => return Ok(self.quit()),
+            };
+            match line.parse() {
+                Ok(Answer::Yes) => return Ok(Some(rename_info.new_path.clone())),
+                Ok(Answer::No) => return Ok(None),
+                Ok(Answer::All) => {
+                    self.state.set(State::All);
+                    return Ok(Some(rename_info.new_path.clone()));
+                }
+                Ok(Answer::Quit) => return Ok(self.quit()),
+                Ok(Answer::Edit) => {
+                    if let Some(new_path) = self.edit(rename_info).await? {
+                        return Ok(Some(new_path));
+                    }
+                }
+                Err(()) => eprintln!("Unknown answer `{}`", line.trim()),
+            }
+        }
+    }
+
+    /// the target with the typed name. An empty or invalid name or an existing target asks again
+    async fn edit(&self, rename_info: &RenameInfo) -> io::Result<Option<PathBuf>> {
+        eprint!("New name: ");
+        let line = match self.read_line()? {
+            Some(line) => line,
+            None => return Ok(self.quit()),
+        };
+        let name = line.trim_end_matches(&['\r', '\n'][..]);
+        if name.is_empty() {
+            return Ok(None);
+        }
+        if name == "." || name == ".." || name.contains('/') {
+            eprintln!("The name `{}` is invalid", name);
+            return Ok(None);
+        }
+        let new_path = rename_info.new_path.with_file_name(name);
+        if let Err(error) = fs::check_sibling_collision(&rename_info.old_file.path, &new_path).await
+        {
+            eprintln!("{}", error);
+            return Ok(None);
+        }
+        Ok(Some(new_path))
+    }
+
+    fn quit(&self) -> Option<PathBuf> {
+        self.state.set(State::Quit);
+        None
+    }
+
+    /// `None` at the end of the input
+    fn read_line(&self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.input.borrow_mut().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+}
@@ -0,0 +1,128 @@
+use super::*;
+use crate::fs::FileInfo;
+use crate::utils::TempDir;
+use std::io::Cursor;
+
+fn prompt(input: &str) -> Prompt {
+    Prompt::new(Box::new(Cursor::new(input.to_string())))
+}
+
+fn rename_info(old_path: &str, new_path: &str) -> RenameInfo {
+    RenameInfo {
+        old_file: FileInfo::file(PathBuf::from(old_path)),
+        new_path: PathBuf::from(new_path),
+        explanation: None,
+    }
+}
+
+#[test]
+fn parse_answers() {
+    assert_eq!("y\n".parse(), Ok(Answer::Yes));
+    assert_eq!("YES".parse(), Ok(Answer::Yes));
+    assert_eq!("\n".parse(), Ok(Answer::No));
+    assert_eq!("a".parse(), Ok(Answer::All));
+    assert_eq!("quit".parse(), Ok(Answer::Quit));
+    assert_eq!(" e ".parse(), Ok(Answer::Edit));
+    assert_eq!("maybe".parse::<Answer>(), Err(()));
+}
+
+#[async_std::test]
+async fn confirm_and_skip() {
+    let prompt = prompt("y\nn\n\n");
+
+    assert_eq!(
+        prompt
+            .confirm(&rename_info("dir/a", "dir/b"))
+            .await
+            .unwrap(),
+        Some(PathBuf::from("dir/b"))
+    );
+    assert_eq!(
+        prompt
+            .confirm(&rename_info("dir/c", "dir/d"))
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        prompt
+            .confirm(&rename_info("dir/e", "dir/f"))
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[async_std::test]
+async fn confirm_all() {
+    let prompt = prompt("a\n");
+
+    assert_eq!(
+        prompt.confirm(&rename_info("a", "b")).await.unwrap(),
+        Some(PathBuf::from("b"))
+    );
+    assert_eq!(
+        prompt.confirm(&rename_info("c", "d")).await.unwrap(),
+        Some(PathBuf::from("d"))
+    );
+}
+
+#[async_std::test]
+async fn quit() {
+    let prompt = prompt("q\ny\n");
+
+    assert_eq!(prompt.confirm(&rename_info("a", "b")).await.unwrap(), None);
+    assert_eq!(prompt.confirm(&rename_info("c", "d")).await.unwrap(), None);
+}
+
+#[async_std::test]
+async fn quit_at_end_of_input() {
+    let prompt = prompt("");
+
+    assert_eq!(prompt.confirm(&rename_info("a", "b")).await.unwrap(), None);
+    assert_eq!(prompt.state.get(), State::Quit);
+}
+
+#[async_std::test]
+async fn edit_name() {
+    let prompt = prompt("e\nc.txt\n");
+
+    assert_eq!(
+        prompt
+            .confirm(&rename_info("dir/a", "dir/b"))
+            .await
+            .unwrap(),
+        Some(PathBuf::from("dir/c.txt"))
+    );
+}
+
+#[async_std::test]
+async fn ask_again() {
+    let prompt = prompt("maybe\ne\n\ne\nsub/c\ne\n..\ny\n");
+
+    assert_eq!(
+        prompt
+            .confirm(&rename_info("dir/a", "dir/b"))
+            .await
+            .unwrap(),
+        Some(PathBuf::from("dir/b"))
+    );
+}
+
+#[async_std::test]
+async fn edit_existing_target() {
+    let dir = TempDir::new();
+    let old_path = dir.file("a_1", "");
+    dir.file("keep", "");
+    let prompt = prompt("e\nkeep\ne\nother\n");
+    let rename_info = RenameInfo {
+        old_file: FileInfo::file(PathBuf::from(old_path)),
+        new_path: PathBuf::from(dir.join("a-1")),
+        explanation: None,
+    };
+
+    assert_eq!(
+        prompt.confirm(&rename_info).await.unwrap(),
+        Some(PathBuf::from(dir.join("other")))
+    );
+}
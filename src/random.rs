@@ -0,0 +1,92 @@
+use async_std::path::Path;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+#[cfg(test)]
+#[path = "./random_test.rs"]
+pub mod random_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("There are no unique values of the placeholder `{0}` left")]
+    Exhausted(String),
+}
+
+/// number of tries to generate a value, which isn't used by another file
+const MAX_ATTEMPTS: usize = 1000;
+
+/// characters of `${rand}`, which are safe on case-insensitive file systems
+const CHARSET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Kind of a random placeholder like `${uuid}` or `${rand:8}`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Uuid,
+    Chars(usize),
+}
+
+impl Kind {
+    fn generate(self, rng: &mut ChaCha8Rng) -> String {
+        match self {
+            Self::Uuid => uuid::Builder::from_random_bytes(rng.gen())
+                .into_uuid()
+                .to_string(),
+            Self::Chars(len) => (0..len)
+                .map(|_| char::from(CHARSET[rng.gen_range(0..CHARSET.len())]))
+                .collect(),
+        }
+    }
+
+    fn placeholder(self) -> String {
+        match self {
+            Self::Uuid => "${uuid}".to_string(),
+            Self::Chars(len) => format!("${{rand:{}}}", len),
+        }
+    }
+}
+
+/// Generator of the random placeholders, whose values are unique within the batch
+#[derive(Debug)]
+pub struct Generator {
+    seed: u64,
+    used: RefCell<HashSet<String>>,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            used: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// every file gets its own generator derived from the seed,
+    /// so that the values don't depend on the order in which the files are processed
+    fn rng(&self, path: &Path) -> ChaCha8Rng {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.to_le_bytes());
+        hasher.update(path.as_os_str().as_encoded_bytes());
+        ChaCha8Rng::from_seed(hasher.finalize().into())
+    }
+
+    /// generates a value for every placeholder of the file
+    pub fn generate(
+        &self,
+        path: &Path,
+        kinds: impl Iterator<Item = Kind>,
+    ) -> Result<Vec<String>, Error> {
+        let mut rng = self.rng(path);
+        let mut used = self.used.borrow_mut();
+        kinds
+            .map(|kind| {
+                (0..MAX_ATTEMPTS)
+                    .map(|_| kind.generate(&mut rng))
+                    .find(|value| used.insert(value.clone()))
+                    .ok_or_else(|| Error::Exhausted(kind.placeholder()))
+            })
+            .collect()
+    }
+}
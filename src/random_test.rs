@@ -0,0 +1,51 @@
+use super::*;
+
+fn generate(generator: &Generator, path: &str, kinds: &[Kind]) -> Vec<String> {
+    generator
+        .generate(Path::new(path), kinds.iter().copied())
+        .unwrap()
+}
+
+#[test]
+fn same_seed_same_values() {
+    let kinds = [Kind::Uuid, Kind::Chars(8)];
+
+    assert_eq!(
+        generate(&Generator::new(42), "dir/foo", &kinds),
+        generate(&Generator::new(42), "dir/foo", &kinds)
+    );
+    assert_ne!(
+        generate(&Generator::new(42), "dir/foo", &kinds),
+        generate(&Generator::new(43), "dir/foo", &kinds)
+    );
+}
+
+#[test]
+fn generate_formats() {
+    let values = generate(
+        &Generator::new(42),
+        "dir/foo",
+        &[Kind::Uuid, Kind::Chars(12)],
+    );
+
+    assert_eq!(
+        uuid::Uuid::parse_str(&values[0]).unwrap().get_version_num(),
+        4
+    );
+    assert_eq!(values[1].len(), 12);
+    assert!(values[1].bytes().all(|byte| CHARSET.contains(&byte)));
+}
+
+#[test]
+fn unique_values() {
+    let generator = Generator::new(42);
+    let values: HashSet<_> = (0..CHARSET.len())
+        .flat_map(|index| generate(&generator, &format!("dir/{}", index), &[Kind::Chars(1)]))
+        .collect();
+
+    assert_eq!(values.len(), CHARSET.len());
+    assert_matches!(
+        generator.generate(Path::new("dir/foo"), std::iter::once(Kind::Chars(1))),
+        Err(Error::Exhausted(_))
+    );
+}
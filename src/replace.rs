@@ -1,63 +1,787 @@
-use async_std::path::{Path, PathBuf};
-use regex::{Regex, RegexBuilder};
+use crate::case;
+use crate::extension;
+use crate::fs::FileInfo;
+use crate::matcher::{self, Groups, Matcher, Selection, Syntax};
+use crate::media::{self, TagKind};
+use crate::normalize;
+use crate::random;
+use crate::rules::{self, FileKind, RuleSet};
+use crate::slug;
+use crate::template::{self, Ambiguity, Context, GroupRef, PathSource, Template};
+use crate::wildcard;
+use async_std::path::{Component, Path, PathBuf};
+use async_std::{fs, io};
+use bool_ext::BoolExt;
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use globset::{Glob, GlobMatcher};
+use miette::{Diagnostic, SourceSpan};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+use std::time::SystemTime;
 
 #[cfg(test)]
 #[path = "./replace_test.rs"]
 pub mod replace_test;
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Diagnostic)]
 pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Matcher(#[from] matcher::Error),
+    #[error(transparent)]
+    Template(#[from] template::Error),
+    #[error(transparent)]
+    Media(#[from] media::Error),
+    #[error(transparent)]
+    Random(#[from] random::Error),
+    #[error(transparent)]
+    Rules(#[from] rules::Error),
+    #[error(transparent)]
+    Glob(#[from] globset::Error),
+    #[error(transparent)]
+    Wildcard(#[from] wildcard::Error),
+    #[error("The rule `{name}` is invalid: {source}")]
+    InvalidRule {
+        name: String,
+        #[source]
+        #[diagnostic_source]
+        source: Box<Self>,
+    },
+    #[error("The search pattern has no group `{group}`{hint}")]
+    UnknownGroup { group: String, hint: String },
+    #[error("The sequence placeholder isn't supported in `{}`", .0.to_string_lossy())]
+    DirSequence(PathBuf),
+    #[error("The sequence number of file {position} exceeds {} with the start {start} and the step {step}", u64::MAX)]
+    SequenceOverflow {
+        position: u64,
+        start: u64,
+        step: u64,
+    },
     #[error("The filename `{}` is invalid", .0.to_string_lossy())]
     InvalidFileName(PathBuf),
     #[error("There is no parent of `{}`", .0.to_string_lossy())]
     NoParent(PathBuf),
     #[error("There is a conversion error in `{}` to UTF-8", .0.to_string_lossy())]
     Utf8Invalid(PathBuf),
+    #[error("The new filename of `{}` is empty", .0.to_string_lossy())]
+    EmptyFileName(PathBuf),
+    #[error("The placeholder `$target` needs a symlink, but `{}` is none", .0.to_string_lossy())]
+    NoSymlink(PathBuf),
+    #[error("Only the content of files can be hashed, but `{}` is none", .0.to_string_lossy())]
+    HashNoFile(PathBuf),
+}
+
+impl std::borrow::Borrow<dyn Diagnostic> for Box<Error> {
+    fn borrow(&self) -> &(dyn Diagnostic + 'static) {
+        self.as_ref()
+    }
+}
+
+/// Likely mistakes in valid patterns
+#[derive(thiserror::Error, Debug, Diagnostic)]
+pub enum Warning {
+    #[error("`${reference}` refers to the group `{reference}`")]
+    #[diagnostic(
+        severity(Warning),
+        help("Write `{suggestion}` for the group `{group}`")
+    )]
+    AmbiguousGroup {
+        reference: String,
+        group: String,
+        suggestion: String,
+    },
+    #[error("The `.` in the search pattern matches any character")]
+    #[diagnostic(
+        severity(Warning),
+        help("Write `\\.` for a dot or search for the plain text with `-F`")
+    )]
+    UnescapedDot {
+        #[source_code]
+        pattern: String,
+        #[label("any character")]
+        span: SourceSpan,
+    },
+    #[error("The search pattern `{pattern}` has groups, but the replacement uses none")]
+    #[diagnostic(
+        severity(Warning),
+        help("A shell removes `$1` within double quotes, so quote the replacement like '$1'")
+    )]
+    UnusedGroups { pattern: String },
+}
+
+impl From<Ambiguity> for Warning {
+    fn from(ambiguity: Ambiguity) -> Self {
+        Self::AmbiguousGroup {
+            suggestion: ambiguity.suggestion(),
+            reference: ambiguity.reference,
+            group: ambiguity.group,
+        }
+    }
+}
+
+/// How a rule changed a name, which is shown by `--explain`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub pattern: String,
+    /// the replaced matches
+    pub matches: Vec<Match>,
+    /// the name after the rule
+    pub result: String,
+}
+
+/// A replaced match of a rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    /// the byte range in the name before the rule
+    pub span: Range<usize>,
+    pub text: String,
+    /// the values of the capture groups by their name or index
+    pub groups: Vec<(String, Option<String>)>,
+    pub replacement: String,
+}
+
+impl Match {
+    fn new(
+        search: &dyn Matcher,
+        span: Range<usize>,
+        groups: &dyn Groups,
+        replacement: &str,
+    ) -> Self {
+        Self {
+            span,
+            text: groups.get(0).unwrap_or_default().to_string(),
+            groups: search
+                .capture_names()
+                .into_iter()
+                .enumerate()
+                .skip(1)
+                .map(|(index, name)| {
+                    let value = groups.get(index).map(str::to_string);
+                    (
+                        name.map_or_else(|| index.to_string(), str::to_string),
+                        value,
+                    )
+                })
+                .collect(),
+            replacement: replacement.to_string(),
+        }
+    }
+}
+
+/// A compiled rule of the renaming pipeline
+#[derive(Debug, Clone)]
+struct Rule {
+    search: Rc<dyn Matcher>,
+    template: Template,
+    types: Vec<FileKind>,
+    glob: Option<GlobMatcher>,
+    stop: bool,
+    /// the replacement has no groups or placeholders
+    literal: bool,
+}
+
+impl Rule {
+    fn new(rule: &rules::Rule, syntax: Syntax, random_offset: usize) -> Result<Self, Error> {
+        let search: Rc<dyn Matcher> = matcher::compile(&rule.pattern, syntax)?.into();
+        let template = if rule.literal {
+            Template::literal(&rule.replacement)
+        } else {
+            Template::new(&rule.replacement, search.as_ref())?.with_random_offset(random_offset)
+        };
+        check_groups(&template, search.as_ref())?;
+        Ok(Self {
+            search,
+            template,
+            types: rule.types.clone(),
+            glob: rule
+                .glob
+                .as_deref()
+                .map(|glob| Ok::<_, Error>(Glob::new(glob)?.compile_matcher()))
+                .transpose()?,
+            stop: rule.stop,
+            literal: rule.literal,
+        })
+    }
+
+    /// the type and glob conditions hold for the file with its original name
+    fn applies(&self, file: &FileInfo, file_name: &str) -> bool {
+        let is_type = self.types.is_empty()
+            || self.types.iter().any(|kind| match kind {
+                FileKind::File => file.file_type.is_file(),
+                FileKind::Dir => file.file_type.is_dir(),
+                FileKind::Symlink => file.file_type.is_symlink(),
+            });
+        is_type
+            && self
+                .glob
+                .as_ref()
+                .is_none_or(|glob| glob.is_match(file_name))
+    }
+}
+
+/// a reference to a missing group would be replaced by nothing
+fn check_groups(template: &Template, search: &dyn Matcher) -> Result<(), Error> {
+    match template.groups().find(|group| !group.exists(search)) {
+        Some(group) => Err(Error::UnknownGroup {
+            group: group.to_string(),
+            hint: template
+                .ambiguities()
+                .iter()
+                .find(|ambiguity| GroupRef::parse(&ambiguity.reference) == *group)
+                .map(|ambiguity| {
+                    format!(
+                        ". Write `{}` for the group `{}`",
+                        ambiguity.suggestion(),
+                        ambiguity.group
+                    )
+                })
+                .unwrap_or_default(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// compiles the rules of a file after the `previous` ones.
+/// Errors name the rule by its name or position in the file
+fn compile_rules(
+    definitions: &[rules::Rule],
+    previous: &mut Vec<Rule>,
+    syntax: Syntax,
+) -> Result<(), Error> {
+    let mut random_offset = random_count(previous);
+    for (index, rule) in definitions.iter().enumerate() {
+        let rule = Rule::new(rule, syntax, random_offset).map_err(|error| Error::InvalidRule {
+            name: rule
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", index + 1)),
+            source: Box::new(error),
+        })?;
+        random_offset += rule.template.random_kinds().count();
+        previous.push(rule);
+    }
+    Ok(())
+}
+
+fn templates(rules: &[Rule]) -> impl Iterator<Item = &Template> {
+    rules.iter().map(|rule| &rule.template)
+}
+
+/// the number of random placeholders, which the rules already use
+fn random_count(rules: &[Rule]) -> usize {
+    templates(rules)
+        .map(|template| template.random_kinds().count())
+        .sum()
+}
+
+/// The effective rules for the entries of a directory
+#[derive(Debug)]
+struct Scope {
+    /// the merged rules of the `.frsrules` files from the base path down to the directory
+    definitions: Vec<rules::Rule>,
+    /// the global rules followed by the compiled definitions
+    rules: Rc<Vec<Rule>>,
 }
 
 #[derive(Debug)]
 pub struct Replacer {
-    search: Regex,
-    replace_pattern: String,
+    base_path: PathBuf,
+    normalize: Option<normalize::Form>,
+    /// the rules of the command line and then of the rules file, which are applied in order
+    rules: Rc<Vec<Rule>>,
+    /// the number of the first rules, which are from the command line
+    cli_rules: usize,
+    syntax: Syntax,
+    /// the matches of every rule, which are replaced
+    selection: Selection,
+    /// the matches and groups of every file are recorded
+    explain: bool,
+    /// the scopes of the visited directories, if `.frsrules` files are discovered
+    scopes: Option<RefCell<HashMap<PathBuf, Rc<Scope>>>>,
+    sequence_start: u64,
+    sequence_step: u64,
+    utc: bool,
+    now: SystemTime,
+    random: random::Generator,
+    tag_fallback: Option<String>,
+    case: Option<(case::Style, case::Part)>,
+    slug_separator: Option<String>,
+    fix_extensions: bool,
 }
 
 impl Replacer {
-    pub fn new(opts: &super::cli::Cli) -> Result<Self, regex::Error> {
+    pub fn new(opts: &super::cli::Cli) -> Result<Self, Error> {
+        let syntax = Syntax {
+            case_insensitive: opts.case_insensetive,
+            fancy: opts.fancy,
+        };
+        let mut rules = Vec::new();
+        for (pattern, replacement) in opts.patterns() {
+            let rule = rules::Rule {
+                name: None,
+                pattern: if opts.glob {
+                    wildcard::to_regex(pattern)?
+                } else if opts.fixed_strings {
+                    regex::escape(pattern)
+                } else {
+                    pattern.to_string()
+                },
+                replacement: replacement.to_string(),
+                literal: opts.literal_replacement,
+                types: Vec::new(),
+                glob: None,
+                stop: false,
+            };
+            let rule = Rule::new(&rule, syntax, random_count(&rules))?;
+            rules.push(rule);
+        }
+        if let Some(rules_file) = &opts.rules {
+            let rule_set = RuleSet::load(rules_file.as_ref())?;
+            compile_rules(&rule_set.rules, &mut rules, syntax)?;
+        }
+
         Ok(Self {
-            search: RegexBuilder::new(&opts.search_pattern.clone())
-                .case_insensitive(opts.case_insensetive)
-                .build()?,
-            replace_pattern: opts.replace_pattern.clone(),
+            base_path: opts.base_path.clone(),
+            normalize: opts.normalize,
+            cli_rules: opts.patterns().len(),
+            rules: Rc::new(rules),
+            syntax,
+            selection: opts.selection(),
+            explain: opts.explain,
+            scopes: opts.traverse_tree.some_with(Default::default),
+            sequence_start: opts.sequence_start,
+            sequence_step: opts.sequence_step,
+            utc: opts.utc,
+            now: SystemTime::now(),
+            random: random::Generator::new(opts.seed.unwrap_or_else(rand::random)),
+            tag_fallback: opts.tag_fallback.clone(),
+            case: opts.case.map(|style| (style, opts.case_part)),
+            slug_separator: opts.slugify.some_with(|| opts.slug_separator.clone()),
+            fix_extensions: opts.fix_extensions,
+        })
+    }
+
+    /// the unbraced references like `$1a` of the command line and rules file, which are valid,
+    /// but could mean a shorter group followed by text.
+    /// Unused groups are only reported for the command line, whose shell could have removed them
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let pattern = rule.search.as_str();
+            if let Some(dot) = matcher::unescaped_dot(pattern) {
+                warnings.push(Warning::UnescapedDot {
+                    pattern: pattern.to_string(),
+                    span: (dot, 1).into(),
+                });
+            }
+            // a shell expands `$1` within double quotes, which leaves only text
+            let is_cli_rule = index < self.cli_rules;
+            if is_cli_rule
+                && !rule.literal
+                && !rule.template.has_placeholders()
+                && matcher::has_value_groups(pattern)
+            {
+                warnings.push(Warning::UnusedGroups {
+                    pattern: pattern.to_string(),
+                });
+            }
+            warnings.extend(
+                rule.template
+                    .ambiguities()
+                    .iter()
+                    .cloned()
+                    .map(Warning::from),
+            );
+        }
+        warnings
+    }
+
+    /// the files have to be numbered, if a replace pattern contains a sequence.
+    /// The numbering happens before the `.frsrules` files are discovered
+    pub fn has_sequence(&self) -> bool {
+        templates(&self.rules).any(Template::has_sequence)
+    }
+
+    /// the rules, which apply to the entries of the directory of `path`
+    fn rules_for(&self, path: &Path) -> Result<Rc<Vec<Rule>>, Error> {
+        match (&self.scopes, path.parent()) {
+            (Some(scopes), Some(dir)) => Ok(Rc::clone(&self.scope(scopes, dir)?.rules)),
+            _ => Ok(Rc::clone(&self.rules)),
+        }
+    }
+
+    /// the `.frsrules` file of a directory extends or overrides the rules of its parent,
+    /// which are inherited up to the base path
+    fn scope(
+        &self,
+        scopes: &RefCell<HashMap<PathBuf, Rc<Scope>>>,
+        dir: &Path,
+    ) -> Result<Rc<Scope>, Error> {
+        if let Some(scope) = scopes.borrow().get(dir) {
+            return Ok(Rc::clone(scope));
+        }
+        let inherited = match dir.parent() {
+            Some(parent) if dir != self.base_path && dir.starts_with(&self.base_path) => {
+                Some(self.scope(scopes, parent)?)
+            }
+            _ => None,
+        };
+        let scope = match (RuleSet::load_dir(dir.as_ref())?, inherited) {
+            (None, Some(inherited)) => inherited,
+            (None, None) => Rc::new(Scope {
+                definitions: Vec::new(),
+                rules: Rc::clone(&self.rules),
+            }),
+            (Some(rule_set), inherited) => {
+                let rules_file = dir.join(rules::DIR_RULES);
+                let definitions = rule_set.merge(
+                    inherited
+                        .as_ref()
+                        .map_or(&[], |inherited| &inherited.definitions),
+                );
+                let mut rules = self.rules.to_vec();
+                compile_rules(&definitions, &mut rules, self.syntax).map_err(
+                    |error| match error {
+                        Error::InvalidRule { name, source } => Error::InvalidRule {
+                            name: format!("{} of `{}`", name, rules_file.to_string_lossy()),
+                            source,
+                        },
+                        error => error,
+                    },
+                )?;
+                // the files are numbered before their `.frsrules` are known
+                if templates(&rules[self.rules.len()..]).any(Template::has_sequence) {
+                    return Err(Error::DirSequence(rules_file));
+                }
+                Rc::new(Scope {
+                    definitions,
+                    rules: Rc::new(rules),
+                })
+            }
+        };
+        scopes
+            .borrow_mut()
+            .insert(dir.to_path_buf(), Rc::clone(&scope));
+        Ok(scope)
+    }
+
+    fn sequence(&self, sequence_index: u64) -> Result<u64, Error> {
+        sequence_index
+            .checked_mul(self.sequence_step)
+            .and_then(|offset| offset.checked_add(self.sequence_start))
+            .ok_or(Error::SequenceOverflow {
+                position: sequence_index.saturating_add(1),
+                start: self.sequence_start,
+                step: self.sequence_step,
+            })
+    }
+
+    fn date_time(&self, time: SystemTime) -> DateTime<FixedOffset> {
+        if self.utc {
+            DateTime::<Utc>::from(time).fixed_offset()
+        } else {
+            DateTime::<Local>::from(time).fixed_offset()
+        }
+    }
+
+    /// collects the values of the placeholders for `file`.
+    /// `sequence_index` is the position of the file in the numbering order.
+    /// Only the rules, which replace something in the name, need their placeholders,
+    /// so that conditional rules don't fail on the files they don't apply to
+    pub async fn context(&self, file: &FileInfo, sequence_index: u64) -> Result<Context, Error> {
+        let rules = self.rules_for(&file.path)?;
+        let mut context = Context {
+            sequence: self.sequence(sequence_index)?,
+            now: Some(self.date_time(self.now)),
+            random: self.random.generate(
+                &file.path,
+                templates(&rules).flat_map(Template::random_kinds),
+            )?,
+            ..Default::default()
+        };
+        let file_name = self.file_name(&file.path)?;
+        let mut new_file_name = file_name.to_string();
+        for rule in rules.iter().filter(|rule| rule.applies(file, &file_name)) {
+            if !rule.search.is_match(&new_file_name)? {
+                continue;
+            }
+            self.add_placeholders(file, &rule.template, &mut context)
+                .await?;
+            let replaced =
+                rule.search
+                    .replace(&new_file_name, self.selection, &mut |_, groups| {
+                        rule.template.expand(groups, &context)
+                    })?;
+            // the same chain as in `replace_explained`
+            let is_match = matches!(replaced, Cow::Owned(_));
+            new_file_name = replaced.into_owned();
+            if is_match && rule.stop {
+                break;
+            }
+        }
+        Ok(context)
+    }
+
+    /// adds the values of the placeholders of `template`, which aren't collected yet
+    async fn add_placeholders(
+        &self,
+        file: &FileInfo,
+        template: &Template,
+        context: &mut Context,
+    ) -> Result<(), Error> {
+        if template.has_metadata() && context.modified.is_none() {
+            let metadata = fs::symlink_metadata(&file.path).await?;
+            context.modified = Some(self.date_time(metadata.modified()?));
+            context.created = Some(self.date_time(created(&metadata)?));
+            context.accessed = Some(self.date_time(metadata.accessed()?));
+        }
+        self.path_context(file, template, context).await?;
+        for algorithm in template.hash_algorithms() {
+            if context.hashes.contains_key(&algorithm) {
+                continue;
+            }
+            if !file.file_type.is_file() {
+                return Err(Error::HashNoFile(file.path.clone()));
+            }
+            context
+                .hashes
+                .insert(algorithm, algorithm.digest(&file.path).await?);
+        }
+        // the missing embedded metadata tags grouped by their kind
+        let mut tags = HashMap::<_, Vec<_>>::new();
+        for (kind, tag) in template.tags() {
+            let is_read = context
+                .tags
+                .get(&kind)
+                .is_some_and(|values| values.contains_key(tag));
+            if !is_read && !tags.get(&kind).is_some_and(|tags| tags.contains(&tag)) {
+                tags.entry(kind).or_default().push(tag);
+            }
+        }
+        for (kind, tags) in tags {
+            let values = self.read_tags(&file.path, kind, &tags)?;
+            context.tags.entry(kind).or_default().extend(values);
+        }
+        Ok(())
+    }
+
+    /// collects the location of the file relative to the base path
+    async fn path_context(
+        &self,
+        file: &FileInfo,
+        template: &Template,
+        context: &mut Context,
+    ) -> Result<(), Error> {
+        if (template.has_path(PathSource::Parent) || template.has_path(PathSource::Grandparent))
+            && context.parent.is_none()
+        {
+            // the base path could be `.` or `..`, which have no names
+            let dir = file
+                .path
+                .parent()
+                .ok_or_else(|| Error::NoParent(file.path.clone()))?
+                .canonicalize()
+                .await?;
+            context.parent = lossy_file_name(&dir);
+            context.grandparent = dir.parent().and_then(lossy_file_name);
+        }
+        // the depth is at least 1 once it is collected
+        if (template.has_path(PathSource::Depth) || template.has_path(PathSource::RelPath))
+            && context.depth == 0
+        {
+            let relpath = file
+                .path
+                .strip_prefix(&self.base_path)
+                .unwrap_or(&file.path);
+            context.relpath = relpath
+                .parent()
+                .into_iter()
+                .flat_map(Path::components)
+                .filter_map(|component| match component {
+                    Component::Normal(dir) => Some(dir.to_string_lossy().into_owned()),
+                    _ => None,
+                })
+                .collect();
+            context.depth = context.relpath.len() + 1;
+        }
+        if template.has_path(PathSource::Target) && context.target.is_none() {
+            if !file.file_type.is_symlink() {
+                return Err(Error::NoSymlink(file.path.clone()));
+            }
+            let target = fs::read_link(&file.path).await?;
+            context.target = Some(
+                lossy_file_name(&target)
+                    .unwrap_or_else(|| target.to_string_lossy().replace('/', "_")),
+            );
+        }
+        Ok(())
+    }
+
+    /// files without a tag get the fallback value or are skipped with an error
+    fn read_tags(
+        &self,
+        path: &Path,
+        kind: TagKind,
+        tags: &[&str],
+    ) -> Result<HashMap<String, String>, Error> {
+        let values = media::read(path.as_ref(), kind, tags)?;
+        tags.iter()
+            .zip(values)
+            .map(|(tag, value)| {
+                let value = value.or_else(|| self.tag_fallback.clone()).ok_or_else(|| {
+                    media::Error::MissingTag {
+                        kind,
+                        tag: tag.to_string(),
+                        path: path.into(),
+                    }
+                })?;
+                Ok((tag.to_string(), value))
+            })
+            .collect()
+    }
+
+    /// renamings could overwrite siblings in another normalization form
+    pub fn is_normalizing(&self) -> bool {
+        self.normalize.is_some()
+    }
+
+    /// the filename in the selected normalization form
+    fn file_name<'a>(&self, file: &'a Path) -> Result<Cow<'a, str>, Error> {
+        let file_name = file
+            .file_name()
+            .ok_or_else(|| Error::InvalidFileName(file.to_path_buf()))?
+            .to_str()
+            .ok_or_else(|| Error::Utf8Invalid(PathBuf::from(file.file_name().unwrap())))?;
+        Ok(match self.normalize {
+            Some(form) => Cow::Owned(form.apply(file_name)),
+            None => Cow::Borrowed(file_name),
         })
     }
 
-    pub fn is_match(&self, file: &Path) -> Result<bool, Error> {
-        Ok(self.search.is_match(
-            file.file_name()
-                .ok_or_else(|| Error::InvalidFileName(file.to_path_buf()))?
-                .to_str()
-                .ok_or_else(|| Error::Utf8Invalid(PathBuf::from(file.file_name().unwrap())))?,
-        ))
+    /// a file matches if any applying rule does or there is none.
+    /// The discovered `.frsrules` files are never renamed
+    pub fn is_match(&self, file: &FileInfo) -> Result<bool, Error> {
+        let file_name = self.file_name(&file.path)?;
+        if self.scopes.is_some() && file_name == rules::DIR_RULES {
+            return Ok(false);
+        }
+        let rules = self.rules_for(&file.path)?;
+        for rule in rules.iter().filter(|rule| rule.applies(file, &file_name)) {
+            if rule.search.is_match(&file_name)? {
+                return Ok(true);
+            }
+        }
+        Ok(rules.is_empty())
+    }
+
+    pub fn replace(&self, file: &FileInfo, context: &Context) -> Result<PathBuf, Error> {
+        self.replace_explained(file, context, None)
     }
 
-    pub fn replace(&self, file: &Path) -> Result<PathBuf, Error> {
-        let mut new_path = file
+    /// records the matches of every rule into `steps`
+    fn replace_explained(
+        &self,
+        file: &FileInfo,
+        context: &Context,
+        mut steps: Option<&mut Vec<Step>>,
+    ) -> Result<PathBuf, Error> {
+        let path = &file.path;
+        let mut new_path = path
             .parent()
-            .ok_or_else(|| Error::NoParent(file.to_path_buf()))?
+            .ok_or_else(|| Error::NoParent(path.to_path_buf()))?
             .to_path_buf();
-        new_path.push(
-            self.search
-                .replace_all(
-                    file.file_name()
-                        .ok_or_else(|| Error::InvalidFileName(file.to_path_buf()))?
-                        .to_str()
-                        .ok_or_else(|| {
-                            Error::Utf8Invalid(PathBuf::from(file.file_name().unwrap()))
-                        })?,
-                    self.replace_pattern.as_str(),
-                )
-                .into_owned(),
-        );
+        let file_name = self.file_name(path)?;
+        let mut new_file_name = file_name.to_string();
+        for rule in self
+            .rules_for(path)?
+            .iter()
+            .filter(|rule| rule.applies(file, &file_name))
+        {
+            let mut matches = Vec::new();
+            let replaced =
+                rule.search
+                    .replace(&new_file_name, self.selection, &mut |range, groups| {
+                        let replacement = rule.template.expand(groups, context);
+                        if steps.is_some() {
+                            matches.push(Match::new(
+                                rule.search.as_ref(),
+                                range,
+                                groups,
+                                &replacement,
+                            ));
+                        }
+                        replacement
+                    })?;
+            // nothing is replaced without a match
+            let is_match = matches!(replaced, Cow::Owned(_));
+            new_file_name = replaced.into_owned();
+            if let (Some(steps), true) = (steps.as_mut(), is_match) {
+                steps.push(Step {
+                    pattern: rule.search.as_str().to_string(),
+                    matches,
+                    result: new_file_name.clone(),
+                });
+            }
+            if is_match && rule.stop {
+                break;
+            }
+        }
+        if let Some((style, part)) = self.case {
+            new_file_name = part.map(&new_file_name, |text| style.convert(text));
+        }
+        if let Some(separator) = &self.slug_separator {
+            new_file_name = slug::slugify(&new_file_name, separator);
+        }
+        if new_file_name.is_empty() {
+            return Err(Error::EmptyFileName(path.to_path_buf()));
+        }
+        new_path.push(new_file_name);
         Ok(new_path)
     }
+
+    /// replaces the path and then applies the content based renamings.
+    /// With `--explain` the steps of the rules are returned as well
+    pub async fn replace_file(
+        &self,
+        file: &FileInfo,
+        sequence_index: u64,
+    ) -> Result<(PathBuf, Option<Vec<Step>>), Error> {
+        let context = self.context(file, sequence_index).await?;
+        let mut steps = self.explain.some_with(Vec::new);
+        let new_path = self.replace_explained(file, &context, steps.as_mut())?;
+        if !self.fix_extensions || !file.file_type.is_file() {
+            return Ok((new_path, steps));
+        }
+
+        let new_path = match extension::detect(&file.path).await? {
+            Some(detected) => extension::fix(&new_path, detected),
+            None => new_path,
+        };
+        Ok((new_path, steps))
+    }
+}
+
+fn lossy_file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+}
+
+/// the creation time falls back to the last status change on unix
+fn created(metadata: &fs::Metadata) -> io::Result<SystemTime> {
+    #[cfg(unix)]
+    if metadata.created().is_err() {
+        use std::os::unix::fs::MetadataExt;
+        use std::time::{Duration, UNIX_EPOCH};
+        return Ok(UNIX_EPOCH
+            + Duration::new(
+                metadata.ctime().max(0) as u64,
+                metadata.ctime_nsec().max(0) as u32,
+            ));
+    }
+    metadata.created()
 }
@@ -1,26 +1,53 @@
 use super::*;
 use crate::cli::cli_test::empty_cli;
+use crate::fs::FileInfo;
+use crate::utils::TempDir;
 use std::ffi::OsStr;
 
-pub fn empty_replacer() -> Replacer {
+fn replacer(search: &str, replace: &str) -> Replacer {
+    let search = regex::Regex::new(search).unwrap();
+    let template = Template::new(replace, &search).unwrap();
     Replacer {
-        search: Regex::new("(.+)").unwrap(),
-        replace_pattern: "${1}".to_string(),
+        base_path: PathBuf::from("."),
+        normalize: None,
+        rules: Rc::new(vec![Rule {
+            search: Rc::new(search),
+            template,
+            types: Vec::new(),
+            glob: None,
+            stop: false,
+            literal: false,
+        }]),
+        cli_rules: 1,
+        syntax: Syntax::default(),
+        selection: Selection::All,
+        explain: false,
+        scopes: None,
+        sequence_start: 1,
+        sequence_step: 1,
+        utc: true,
+        now: SystemTime::UNIX_EPOCH,
+        random: random::Generator::new(0),
+        tag_fallback: None,
+        case: None,
+        slug_separator: None,
+        fix_extensions: false,
     }
 }
 
+pub fn empty_replacer() -> Replacer {
+    replacer("(.+)", "${1}")
+}
+
 pub fn restrictive_replacer() -> Replacer {
-    Replacer {
-        search: Regex::new("_(.+)").unwrap(),
-        replace_pattern: "${1}".to_string(),
-    }
+    replacer("_(.+)", "${1}")
 }
 
 #[test]
 fn valid_regex() {
     let mut cli = empty_cli();
-    cli.search_pattern = "(.+)".to_string();
-    cli.replace_pattern = "${1}".to_string();
+    cli.search_pattern = Some("(.+)".to_string());
+    cli.replace_pattern = Some("${1}".to_string());
 
     assert_matches!(Replacer::new(&cli), Ok(_));
 }
@@ -28,24 +55,66 @@ fn valid_regex() {
 #[test]
 fn invalid_regex() {
     let mut cli = empty_cli();
-    cli.search_pattern = "(.+".to_string();
-    cli.replace_pattern = "${1}".to_string();
+    cli.search_pattern = Some("(.+".to_string());
+    cli.replace_pattern = Some("${1}".to_string());
 
     assert_matches!(Replacer::new(&cli), Err(_));
 }
 
+#[test]
+fn without_search_pattern() {
+    let mut cli = empty_cli();
+    cli.fix_extensions = true;
+    let replacer = Replacer::new(&cli).unwrap();
+
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/test"))),
+        Ok(true)
+    );
+    assert_eq!(
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("dir/test")),
+                &Context::default()
+            )
+            .unwrap(),
+        PathBuf::from("dir/test")
+    );
+}
+
+#[test]
+fn invalid_template() {
+    let mut cli = empty_cli();
+    cli.search_pattern = Some("(.+)".to_string());
+    cli.replace_pattern = Some("${n:x}".to_string());
+
+    assert_matches!(Replacer::new(&cli), Err(Error::Template(_)));
+}
+
+#[test]
+fn has_sequence() {
+    assert!(replacer("(.+)", "${n}_${1}").has_sequence());
+    assert!(!restrictive_replacer().has_sequence());
+}
+
 #[test]
 fn match_matching_filename() {
     let replacer = restrictive_replacer();
 
-    assert_matches!(replacer.is_match(Path::new("dir/_test")), Ok(true));
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/_test"))),
+        Ok(true)
+    );
 }
 
 #[test]
 fn match_non_matching_filenames() {
     let replacer = restrictive_replacer();
 
-    assert_matches!(replacer.is_match(Path::new("dir/test")), Ok(false));
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/test"))),
+        Ok(false)
+    );
 }
 
 #[test]
@@ -53,15 +122,15 @@ fn match_invalid_filenames() {
     let replacer = empty_replacer();
 
     assert_matches!(
-        replacer.is_match(Path::new("..")),
+        replacer.is_match(&FileInfo::file(PathBuf::from(".."))),
         Err(Error::InvalidFileName(_))
     );
     assert_matches!(
-        replacer.is_match(Path::new(".")),
+        replacer.is_match(&FileInfo::file(PathBuf::from("."))),
         Err(Error::InvalidFileName(_))
     );
     assert_matches!(
-        replacer.is_match(Path::new("/")),
+        replacer.is_match(&FileInfo::file(PathBuf::from("/"))),
         Err(Error::InvalidFileName(_))
     );
 }
@@ -73,7 +142,9 @@ fn match_non_utf8_filenames() {
     let replacer = empty_replacer();
 
     assert_matches!(
-        replacer.is_match(Path::new(OsStr::from_bytes(&[0x66, 0x6f, 0x80, 0x6f]))),
+        replacer.is_match(&FileInfo::file(PathBuf::from(OsStr::from_bytes(&[
+            0x66, 0x6f, 0x80, 0x6f
+        ])))),
         Err(Error::Utf8Invalid(_))
     );
 }
@@ -85,9 +156,9 @@ fn match_non_utf8_filenames() {
     let replacer = empty_replacer();
 
     assert_matches!(
-        replacer.is_match(Path::new(OsStr::from_bytes(&[
+        replacer.is_match(&FileInfo::file(PathBuf::from(OsStr::from_bytes(&[
             0x0066, 0x006f, 0xD800, 0x006f
-        ]))),
+        ])))),
         Err(Error::Utf8Invalid(_))
     );
 }
@@ -97,15 +168,27 @@ fn replace_matching_filenames() {
     let replacer = restrictive_replacer();
 
     assert_eq!(
-        replacer.replace(Path::new("../_test")).unwrap(),
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("../_test")),
+                &Context::default()
+            )
+            .unwrap(),
         PathBuf::from("../test")
     );
     assert_eq!(
-        replacer.replace(Path::new("/_foo")).unwrap(),
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("/_foo")), &Context::default())
+            .unwrap(),
         PathBuf::from("/foo")
     );
     assert_eq!(
-        replacer.replace(Path::new("/_foo/_bar")).unwrap(),
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("/_foo/_bar")),
+                &Context::default()
+            )
+            .unwrap(),
         PathBuf::from("/_foo/bar")
     );
 }
@@ -115,15 +198,27 @@ fn replace_non_matching_filenames() {
     let replacer = restrictive_replacer();
 
     assert_eq!(
-        replacer.replace(Path::new("../test")).unwrap(),
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("../test")),
+                &Context::default()
+            )
+            .unwrap(),
         PathBuf::from("../test")
     );
     assert_eq!(
-        replacer.replace(Path::new("/foo")).unwrap(),
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("/foo")), &Context::default())
+            .unwrap(),
         PathBuf::from("/foo")
     );
     assert_eq!(
-        replacer.replace(Path::new("/_foo/bar")).unwrap(),
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("/_foo/bar")),
+                &Context::default()
+            )
+            .unwrap(),
         PathBuf::from("/_foo/bar")
     );
 }
@@ -133,11 +228,11 @@ fn replace_invalid_filenames() {
     let replacer = empty_replacer();
 
     assert_matches!(
-        replacer.replace(Path::new("..")),
+        replacer.replace(&FileInfo::file(PathBuf::from("..")), &Context::default()),
         Err(Error::InvalidFileName(_))
     );
     assert_matches!(
-        replacer.replace(Path::new(".")),
+        replacer.replace(&FileInfo::file(PathBuf::from(".")), &Context::default()),
         Err(Error::InvalidFileName(_))
     );
 }
@@ -146,7 +241,10 @@ fn replace_invalid_filenames() {
 fn replace_no_parent() {
     let replacer = empty_replacer();
 
-    assert_matches!(replacer.replace(Path::new("/")), Err(Error::NoParent(_)));
+    assert_matches!(
+        replacer.replace(&FileInfo::file(PathBuf::from("/")), &Context::default()),
+        Err(Error::NoParent(_))
+    );
 }
 
 #[cfg(any(unix, target_os = "redox"))]
@@ -156,7 +254,10 @@ fn replace_non_utf8_filenames() {
     let replacer = empty_replacer();
 
     assert_matches!(
-        replacer.replace(Path::new(OsStr::from_bytes(&[0x66, 0x6f, 0x80, 0x6f]))),
+        replacer.replace(
+            &FileInfo::file(PathBuf::from(OsStr::from_bytes(&[0x66, 0x6f, 0x80, 0x6f]))),
+            &Context::default()
+        ),
         Err(Error::Utf8Invalid(_))
     );
 }
@@ -168,9 +269,758 @@ fn replace_non_utf8_filenames() {
     let replacer = empty_replacer();
 
     assert_matches!(
-        replacer.replace(Path::new(OsStr::from_bytes(&[
-            0x0066, 0x006f, 0xD800, 0x006f
-        ]))),
+        replacer.replace(
+            &FileInfo::file(PathBuf::from(OsStr::from_bytes(&[
+                0x0066, 0x006f, 0xD800, 0x006f
+            ]))),
+            &Context::default()
+        ),
         Err(Error::Utf8Invalid(_))
     );
 }
+
+#[test]
+fn sequence_overflow() {
+    let mut replacer = replacer("_(.+)", "${n}_${1}");
+    replacer.sequence_start = u64::MAX - 1;
+
+    assert_matches!(replacer.sequence(1), Ok(u64::MAX));
+    assert_matches!(
+        replacer.sequence(2),
+        Err(Error::SequenceOverflow { position: 3, .. })
+    );
+    replacer.sequence_start = 0;
+    replacer.sequence_step = u64::MAX / 2 + 1;
+    assert_matches!(replacer.sequence(1), Ok(_));
+    assert_matches!(replacer.sequence(2), Err(Error::SequenceOverflow { .. }));
+}
+
+#[test]
+fn replace_with_sequence() {
+    let mut replacer = replacer("_(.+)", "${n:03}_${1}");
+    replacer.sequence_start = 10;
+    replacer.sequence_step = 5;
+
+    let context = |sequence_index| Context {
+        sequence: replacer.sequence(sequence_index).unwrap(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("dir/_foo")), &context(0))
+            .unwrap(),
+        PathBuf::from("dir/010_foo")
+    );
+    assert_eq!(
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("dir/_foo")), &context(3))
+            .unwrap(),
+        PathBuf::from("dir/025_foo")
+    );
+}
+
+#[test]
+fn replace_with_case() {
+    let mut replacer = replacer("^IMG_", "");
+    replacer.case = Some((case::Style::Kebab, case::Part::Stem));
+
+    assert_eq!(
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("dir/IMG_HolidayPhoto.JPG")),
+                &Context::default()
+            )
+            .unwrap(),
+        PathBuf::from("dir/holiday-photo.JPG")
+    );
+}
+
+#[test]
+fn replace_with_slug() {
+    let mut replacer = replacer("^(.+) - ", "${1}_");
+    replacer.slug_separator = Some("-".to_string());
+
+    assert_eq!(
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("dir/Ärger - im Café.txt")),
+                &Context::default()
+            )
+            .unwrap(),
+        PathBuf::from("dir/Aerger-im-Cafe.txt")
+    );
+}
+
+#[test]
+fn replace_to_empty_filename() {
+    let replacer = replacer(".+", "");
+
+    assert_matches!(
+        replacer.replace(
+            &FileInfo::file(PathBuf::from("dir/foo")),
+            &Context::default()
+        ),
+        Err(Error::EmptyFileName(_))
+    );
+}
+
+#[test]
+fn match_normalized_filename() {
+    let mut replacer = replacer("^caf\u{e9}$", "cafe");
+
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/cafe\u{301}"))),
+        Ok(false)
+    );
+    replacer.normalize = Some(normalize::Form::Nfc);
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/cafe\u{301}"))),
+        Ok(true)
+    );
+}
+
+#[test]
+fn replace_to_normalized_filename() {
+    let mut cli = empty_cli();
+    cli.normalize = Some(normalize::Form::Nfc);
+    let replacer = Replacer::new(&cli).unwrap();
+
+    assert_eq!(
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("dir/cafe\u{301}")),
+                &Context::default()
+            )
+            .unwrap(),
+        PathBuf::from("dir/caf\u{e9}")
+    );
+}
+
+#[async_std::test]
+async fn context_with_now() {
+    let replacer = replacer("(.+)", "${now:%Y}_$1");
+    let context = replacer
+        .context(&FileInfo::file(PathBuf::from("dir/foo")), 0)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("dir/foo")), &context)
+            .unwrap(),
+        PathBuf::from("dir/1970_foo")
+    );
+}
+
+#[async_std::test]
+async fn context_with_metadata() {
+    let dir = TempDir::new();
+    let path = dir.file("file", "");
+    let file = FileInfo::file(PathBuf::from(path));
+    let replacer = replacer("(.+)", "${mtime:%Y}_$1");
+
+    let context = replacer.context(&file, 0).await.unwrap();
+    assert!(context.modified.is_some());
+    assert!(context.created.is_some());
+}
+
+#[async_std::test]
+async fn context_of_missing_file() {
+    let file = FileInfo::file(PathBuf::from("non_existant/foo"));
+
+    assert_matches!(
+        replacer("(.+)", "${mtime}").context(&file, 0).await,
+        Err(Error::Io(_))
+    );
+    assert_matches!(
+        restrictive_replacer().context(&file, 0).await,
+        Ok(Context { modified: None, .. })
+    );
+}
+
+#[cfg(feature = "media")]
+#[async_std::test]
+async fn context_with_missing_tag() {
+    let dir = TempDir::new();
+    let path = dir.file("song.mp3", "");
+    let file = FileInfo::file(PathBuf::from(path));
+    let mut replacer = replacer("(.+)", "${id3:artist}");
+
+    assert_matches!(
+        replacer.context(&file, 0).await,
+        Err(Error::Media(media::Error::MissingTag { .. }))
+    );
+
+    replacer.tag_fallback = Some("Unknown".to_string());
+    let context = replacer.context(&file, 0).await.unwrap();
+    assert_eq!(
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("dir/foo.mp3")), &context)
+            .unwrap(),
+        PathBuf::from("dir/Unknown")
+    );
+}
+
+#[async_std::test]
+async fn context_with_hash() {
+    let dir = TempDir::new();
+    let path = dir.file("content", "abc");
+    let file = FileInfo::file(PathBuf::from(path));
+    let replacer = replacer("(.+)", "${hash:12}");
+
+    let context = replacer.context(&file, 0).await.unwrap();
+    assert_eq!(
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("dir/foo")), &context)
+            .unwrap(),
+        PathBuf::from("dir/ba7816bf8f01")
+    );
+}
+
+#[async_std::test]
+async fn hash_of_directory() {
+    let dir = TempDir::new();
+    let file = FileInfo::dir(PathBuf::from(dir.path()));
+
+    assert_matches!(
+        replacer("(.+)", "${hash}").context(&file, 0).await,
+        Err(Error::HashNoFile(_))
+    );
+}
+
+#[async_std::test]
+async fn context_with_path() {
+    let base_dir = TempDir::new();
+    let base_path = base_dir.path();
+    let dir = base_path.join("show").join("season1");
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = FileInfo::file(PathBuf::from(dir.join("01.mkv")));
+    let mut replacer = replacer(
+        r"^(\d+)\.mkv$",
+        "${grandparent} - ${parent}e$1 (${depth} ${relpath}).mkv",
+    );
+    replacer.base_path = PathBuf::from(base_path);
+
+    let context = replacer.context(&file, 0).await.unwrap();
+    assert_eq!(
+        replacer.replace(&file, &context).unwrap(),
+        PathBuf::from(dir.join("show - season1e01 (3 show_season1).mkv"))
+    );
+}
+
+#[cfg(unix)]
+#[async_std::test]
+async fn context_with_symlink_target() {
+    let dir = TempDir::new();
+    let path = dir.join("link");
+    std::os::unix::fs::symlink("/media/movie.mkv", &path).unwrap();
+    let replacer = replacer("(.+)", "$target");
+
+    let context = replacer
+        .context(&FileInfo::symlink(PathBuf::from(&path)), 0)
+        .await
+        .unwrap();
+    assert_eq!(context.target.as_deref(), Some("movie.mkv"));
+    assert_matches!(
+        replacer
+            .context(&FileInfo::file(PathBuf::from(path)), 0)
+            .await,
+        Err(Error::NoSymlink(_))
+    );
+}
+
+#[cfg(unix)]
+#[async_std::test]
+async fn context_of_chained_rules() {
+    let dir = TempDir::new();
+    let link = PathBuf::from(dir.join("link_a"));
+    std::os::unix::fs::symlink("/media/movie.mkv", &link).unwrap();
+    let file = FileInfo::file(PathBuf::from(dir.file("file_a", "")));
+    let mut cli = empty_cli();
+    cli.expressions = ["^link_.*", "to_${target}", "^file_", "F_"]
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect();
+    let replacer = Replacer::new(&cli).unwrap();
+    let replace = |file: &FileInfo, context: &Context| replacer.replace(file, context).unwrap();
+
+    let context = replacer.context(&file, 0).await.unwrap();
+    assert_eq!(context.target, None);
+    assert_eq!(replace(&file, &context), PathBuf::from(dir.join("F_a")));
+    let link = FileInfo::symlink(link);
+    let context = replacer.context(&link, 0).await.unwrap();
+    assert_eq!(
+        replace(&link, &context),
+        PathBuf::from(dir.join("to_movie.mkv"))
+    );
+
+    // the second rule matches the name after the first one
+    cli.expressions = ["^file_", "link_", "^link_.*", "to_${target}"]
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect();
+    assert_matches!(
+        Replacer::new(&cli).unwrap().context(&file, 0).await,
+        Err(Error::NoSymlink(_))
+    );
+}
+
+#[cfg(unix)]
+#[async_std::test]
+async fn context_of_conditional_rules() {
+    let dir = TempDir::new();
+    let link = PathBuf::from(dir.join("link_a"));
+    std::os::unix::fs::symlink("/media/movie.mkv", &link).unwrap();
+    let sub_dir = dir.join("dir_a");
+    std::fs::create_dir(&sub_dir).unwrap();
+    let mut cli = empty_cli();
+    cli.rules = Some(PathBuf::from(dir.file(
+        "rules.toml",
+        r#"
+[[rules]]
+pattern = ".+"
+replacement = "to_${target}"
+types = ["symlink"]
+
+[[rules]]
+pattern = "_a$"
+replacement = "_${hash:8}"
+types = ["file"]
+
+[[rules]]
+pattern = "^dir_"
+replacement = "D_"
+"#,
+    )));
+    let replacer = &Replacer::new(&cli).unwrap();
+    let replace = |file: FileInfo| async move {
+        let context = replacer.context(&file, 0).await.unwrap();
+        replacer.replace(&file, &context).unwrap()
+    };
+
+    assert_eq!(
+        replace(FileInfo::symlink(link)).await,
+        PathBuf::from(dir.join("to_movie.mkv"))
+    );
+    assert_eq!(
+        replace(FileInfo::file(PathBuf::from(dir.file("file_a", "abc")))).await,
+        PathBuf::from(dir.join("file_ba7816bf"))
+    );
+    assert_eq!(
+        replace(FileInfo::dir(PathBuf::from(sub_dir))).await,
+        PathBuf::from(dir.join("D_a"))
+    );
+}
+
+#[async_std::test]
+async fn context_with_random() {
+    let file = FileInfo::file(PathBuf::from("dir/foo"));
+    let replacer = replacer("(.+)", "${rand:4}_$uuid");
+
+    let context = replacer.context(&file, 0).await.unwrap();
+    assert_eq!(context.random.len(), 2);
+    assert_eq!(context.random[0].len(), 4);
+}
+
+#[async_std::test]
+async fn seeded_random_is_reproducible() {
+    let mut cli = empty_cli();
+    cli.search_pattern = Some("(.+)".to_string());
+    cli.replace_pattern = Some("${rand}".to_string());
+    cli.seed = Some(7);
+    let file = FileInfo::file(PathBuf::from("dir/foo"));
+
+    assert_eq!(
+        Replacer::new(&cli)
+            .unwrap()
+            .context(&file, 0)
+            .await
+            .unwrap()
+            .random,
+        Replacer::new(&cli)
+            .unwrap()
+            .context(&file, 0)
+            .await
+            .unwrap()
+            .random
+    );
+}
+
+#[test]
+fn replace_with_chained_rules() {
+    let mut cli = empty_cli();
+    cli.expressions = ["^IMG_", "", " ", "_", "(?i)\\.jpeg$", ".jpg"]
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect();
+    let replacer = Replacer::new(&cli).unwrap();
+
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/a b.txt"))),
+        Ok(true)
+    );
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/ab.txt"))),
+        Ok(false)
+    );
+    assert_eq!(
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("dir/IMG_a b.JPEG")),
+                &Context::default()
+            )
+            .unwrap(),
+        PathBuf::from("dir/a_b.jpg")
+    );
+}
+
+#[test]
+fn replace_with_random_in_chained_rules() {
+    let mut cli = empty_cli();
+    cli.expressions = ["^.+$", "${rand:3}_x", "_x$", "_${rand:3}"]
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect();
+    let replacer = Replacer::new(&cli).unwrap();
+    let context = Context {
+        random: vec!["aaa".to_string(), "bbb".to_string()],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        replacer
+            .replace(&FileInfo::file(PathBuf::from("dir/foo")), &context)
+            .unwrap(),
+        PathBuf::from("dir/aaa_bbb")
+    );
+}
+
+#[test]
+fn replace_with_rules_file() {
+    let dir = TempDir::new();
+    let mut cli = empty_cli();
+    cli.rules = Some(PathBuf::from(dir.file(
+        "rules.toml",
+        r#"
+[[rules]]
+name = "photos"
+pattern = "^IMG_"
+types = ["file"]
+glob = "*.jpg"
+stop = true
+
+[[rules]]
+pattern = "_"
+replacement = "-"
+"#,
+    )));
+    let replacer = Replacer::new(&cli).unwrap();
+    let replace = |file: FileInfo| replacer.replace(&file, &Context::default()).unwrap();
+
+    assert_eq!(
+        replace(FileInfo::file(PathBuf::from("dir/IMG_a_b.jpg"))),
+        PathBuf::from("dir/a_b.jpg")
+    );
+    assert_eq!(
+        replace(FileInfo::file(PathBuf::from("dir/IMG_a_b.png"))),
+        PathBuf::from("dir/IMG-a-b.png")
+    );
+    assert_eq!(
+        replace(FileInfo::dir(PathBuf::from("dir/IMG_a_b.jpg"))),
+        PathBuf::from("dir/IMG-a-b.jpg")
+    );
+    assert_matches!(
+        replacer.is_match(&FileInfo::dir(PathBuf::from("dir/IMG.jpg"))),
+        Ok(false)
+    );
+}
+
+#[test]
+fn invalid_rule_in_rules_file() {
+    let dir = TempDir::new();
+    let mut cli = empty_cli();
+    cli.rules = Some(PathBuf::from(
+        dir.file("rules.yaml", "rules:\n  - pattern: a\n  - pattern: (\n"),
+    ));
+
+    assert_matches!(
+        Replacer::new(&cli),
+        Err(Error::InvalidRule { name, .. }) if name == "#2"
+    );
+}
+
+#[test]
+fn replace_with_dir_rules() {
+    let base_dir = TempDir::new();
+    let base_path = base_dir.path();
+    let sub_dir = base_path.join("sub");
+    let isolated_dir = base_path.join("isolated");
+    std::fs::create_dir_all(&sub_dir).unwrap();
+    std::fs::create_dir_all(&isolated_dir).unwrap();
+    std::fs::write(
+        base_path.join(rules::DIR_RULES),
+        "[[rules]]\nname = 'space'\npattern = ' '\nreplacement = '_'\n",
+    )
+    .unwrap();
+    std::fs::write(
+        sub_dir.join(rules::DIR_RULES),
+        "[[rules]]\nname = 'space'\npattern = ' '\nreplacement = '-'\n\n[[rules]]\npattern = 'x'\nreplacement = 'y'\n",
+    )
+    .unwrap();
+    std::fs::write(isolated_dir.join(rules::DIR_RULES), "inherit = false\n").unwrap();
+
+    let mut cli = empty_cli();
+    cli.base_path = PathBuf::from(&base_path);
+    cli.traverse_tree = true;
+    cli.expressions = vec!["^old".to_string(), "new".to_string()];
+    let replacer = Replacer::new(&cli).unwrap();
+    let replace = |path: std::path::PathBuf| {
+        replacer
+            .replace(&FileInfo::file(PathBuf::from(path)), &Context::default())
+            .unwrap()
+    };
+
+    assert_eq!(
+        replace(base_path.join("old a x")),
+        PathBuf::from(base_path.join("new_a_x"))
+    );
+    assert_eq!(
+        replace(sub_dir.join("old a x")),
+        PathBuf::from(sub_dir.join("new-a-y"))
+    );
+    assert_eq!(
+        replace(sub_dir.join("deeper/old a x")),
+        PathBuf::from(sub_dir.join("deeper/new-a-y"))
+    );
+    assert_eq!(
+        replace(isolated_dir.join("old a x")),
+        PathBuf::from(isolated_dir.join("new a x"))
+    );
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from(
+            sub_dir.join(rules::DIR_RULES)
+        ))),
+        Ok(false)
+    );
+}
+
+#[test]
+fn sequence_in_dir_rules() {
+    let base_dir = TempDir::new();
+    let base_path = base_dir.path();
+    std::fs::write(
+        base_path.join(rules::DIR_RULES),
+        "[[rules]]\npattern = '^'\nreplacement = '${n}_'\n",
+    )
+    .unwrap();
+
+    let mut cli = empty_cli();
+    cli.base_path = PathBuf::from(&base_path);
+    cli.traverse_tree = true;
+    let replacer = Replacer::new(&cli).unwrap();
+
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from(base_path.join("a")))),
+        Err(Error::DirSequence(_))
+    );
+}
+
+#[test]
+fn replace_with_glob() {
+    let mut cli = empty_cli();
+    cli.glob = true;
+    cli.search_pattern = Some("IMG_*.{jpg,jpeg}".to_string());
+    cli.replace_pattern = Some("photo_$1.$2".to_string());
+    let replacer = Replacer::new(&cli).unwrap();
+    let file = FileInfo::file(PathBuf::from("dir/IMG_beach.jpeg"));
+
+    assert_matches!(replacer.is_match(&file), Ok(true));
+    assert_eq!(
+        replacer.replace(&file, &Context::default()).unwrap(),
+        PathBuf::from("dir/photo_beach.jpeg")
+    );
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/IMG_beach.png"))),
+        Ok(false)
+    );
+
+    cli.search_pattern = Some("[ab".to_string());
+    assert_matches!(Replacer::new(&cli), Err(Error::Wildcard(_)));
+}
+
+#[test]
+fn replace_with_fixed_strings() {
+    let mut cli = empty_cli();
+    cli.fixed_strings = true;
+    cli.literal_replacement = true;
+    cli.search_pattern = Some("(1).".to_string());
+    cli.replace_pattern = Some("[$1]$".to_string());
+    let replacer = Replacer::new(&cli).unwrap();
+    let file = FileInfo::file(PathBuf::from("dir/a (1).txt"));
+
+    assert_matches!(replacer.is_match(&file), Ok(true));
+    assert_eq!(
+        replacer.replace(&file, &Context::default()).unwrap(),
+        PathBuf::from("dir/a [$1]$txt")
+    );
+    assert_matches!(
+        replacer.is_match(&FileInfo::file(PathBuf::from("dir/a 1.txt"))),
+        Ok(false)
+    );
+}
+
+#[test]
+fn replace_last_match() {
+    let mut replacer = replacer("\\.", "_");
+    replacer.selection = Selection::Last;
+
+    assert_eq!(
+        replacer
+            .replace(
+                &FileInfo::file(PathBuf::from("dir/archive.tar.gz")),
+                &Context::default()
+            )
+            .unwrap(),
+        PathBuf::from("dir/archive.tar_gz")
+    );
+}
+
+#[test]
+fn explain_steps() {
+    let replacer = replacer(r"(\d+)(?P<ext>x)?", "#${1}");
+    let mut steps = Vec::new();
+
+    assert_eq!(
+        replacer
+            .replace_explained(
+                &FileInfo::file(PathBuf::from("dir/a1b22")),
+                &Context::default(),
+                Some(&mut steps)
+            )
+            .unwrap(),
+        PathBuf::from("dir/a#1b#22")
+    );
+    let group = |label: &str, value: Option<&str>| (label.to_string(), value.map(str::to_string));
+    assert_eq!(
+        steps,
+        vec![Step {
+            pattern: r"(\d+)(?P<ext>x)?".to_string(),
+            matches: vec![
+                Match {
+                    span: 1..2,
+                    text: "1".to_string(),
+                    groups: vec![group("1", Some("1")), group("ext", None)],
+                    replacement: "#1".to_string(),
+                },
+                Match {
+                    span: 3..5,
+                    text: "22".to_string(),
+                    groups: vec![group("1", Some("22")), group("ext", None)],
+                    replacement: "#22".to_string(),
+                },
+            ],
+            result: "a#1b#22".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn explain_without_match() {
+    let replacer = replacer("x", "y");
+    let mut steps = Vec::new();
+
+    replacer
+        .replace_explained(
+            &FileInfo::file(PathBuf::from("dir/abc")),
+            &Context::default(),
+            Some(&mut steps),
+        )
+        .unwrap();
+    assert!(steps.is_empty());
+}
+
+#[test]
+fn unknown_group_references() {
+    let error = |search: &str, replace: &str| {
+        let mut cli = empty_cli();
+        cli.search_pattern = Some(search.to_string());
+        cli.replace_pattern = Some(replace.to_string());
+        Replacer::new(&cli).unwrap_err().to_string()
+    };
+
+    assert_eq!(
+        error("(a)(b)", "${3}"),
+        "The search pattern has no group `3`"
+    );
+    assert_eq!(
+        error("(?P<x>a)", "${y}"),
+        "The search pattern has no group `y`"
+    );
+    assert_eq!(
+        error("(a)", "$1a"),
+        "The search pattern has no group `1a`. Write `${1}a` for the group `1`"
+    );
+}
+
+#[test]
+fn literal_replacement_has_no_groups() {
+    let mut cli = empty_cli();
+    cli.literal_replacement = true;
+    cli.search_pattern = Some("a".to_string());
+    cli.replace_pattern = Some("$1".to_string());
+
+    assert_matches!(Replacer::new(&cli), Ok(_));
+}
+
+#[test]
+fn warn_about_ambiguous_group() {
+    let mut cli = empty_cli();
+    cli.search_pattern = Some("(?P<a>.)(?P<ab>.)".to_string());
+    cli.replace_pattern = Some("$ab".to_string());
+    let warnings = Replacer::new(&cli).unwrap().warnings();
+
+    assert_eq!(warnings.len(), 1);
+    assert_matches!(
+        &warnings[0],
+        Warning::AmbiguousGroup { suggestion, .. } if suggestion == "${a}b"
+    );
+}
+
+#[test]
+fn warn_about_likely_mistakes() {
+    let warnings = |search: &str, replace: &str| {
+        let mut cli = empty_cli();
+        cli.search_pattern = Some(search.to_string());
+        cli.replace_pattern = Some(replace.to_string());
+        Replacer::new(&cli).unwrap().warnings()
+    };
+
+    assert_matches!(
+        warnings("IMG.jpg", "x").as_slice(),
+        [Warning::UnescapedDot { .. }]
+    );
+    assert_matches!(
+        warnings("(.+)_(.+)", "_x").as_slice(),
+        [Warning::UnusedGroups { .. }]
+    );
+    assert_matches!(warnings("(.+)_(.+)", "${2}_$1").as_slice(), []);
+    assert_matches!(warnings("(.*)", "${uuid}").as_slice(), []);
+    assert_matches!(warnings("^(IMG|DSC)_", "").as_slice(), []);
+    assert_matches!(
+        warnings("(.*)", "\\Ux").as_slice(),
+        [Warning::UnusedGroups { .. }]
+    );
+}
+
+#[test]
+fn no_unused_groups_warning_for_rules_file() {
+    let dir = TempDir::new();
+    let mut cli = empty_cli();
+    cli.rules = Some(PathBuf::from(dir.file(
+        "rules.toml",
+        "[[rules]]\npattern = '^(\\d+)_'\nreplacement = 'x'\n",
+    )));
+
+    assert_matches!(Replacer::new(&cli).unwrap().warnings().as_slice(), []);
+}
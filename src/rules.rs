@@ -0,0 +1,141 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+#[path = "./rules_test.rs"]
+pub mod rules_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("The rules file `{}` is unreadable: {source}", .path.to_string_lossy())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("The rules file `{}` is invalid: {message}", .path.to_string_lossy())]
+    Parse { path: PathBuf, message: String },
+    #[error("The format of the rules file `{}` is unknown. It has to end with `.toml`, `.yaml` or `.yml`", .0.to_string_lossy())]
+    UnknownFormat(PathBuf),
+}
+
+/// Format of a rules file by its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Type of the files to which a rule is restricted
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+/// A rule as written in the rules file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: Option<String>,
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: String,
+    /// the replacement is inserted without expanding groups or placeholders
+    #[serde(default)]
+    pub literal: bool,
+    /// the rule applies to all types, if none is given
+    #[serde(default)]
+    pub types: Vec<FileKind>,
+    /// a glob matched against the original file name
+    pub glob: Option<String>,
+    /// skips the following rules, if this one matched
+    #[serde(default)]
+    pub stop: bool,
+}
+
+/// name of the rules files, which are discovered in the directories
+pub const DIR_RULES: &str = ".frsrules";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSet {
+    /// the rules of the `.frsrules` files in the parent directories are kept
+    #[serde(default = "default_inherit")]
+    pub inherit: bool,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+fn default_inherit() -> bool {
+    true
+}
+
+impl RuleSet {
+    /// reads the rules file, whose format is determined by the extension
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let format =
+            Format::from_path(path).ok_or_else(|| Error::UnknownFormat(path.to_path_buf()))?;
+        let content = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&content, format).map_err(|message| Error::Parse {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+
+    /// reads the `.frsrules` file of the directory in the TOML format, if there is one
+    pub fn load_dir(dir: &Path) -> Result<Option<Self>, Error> {
+        let path = dir.join(DIR_RULES);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(source) if source.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(Error::Io { path, source }),
+        };
+        Self::parse(&content, Format::Toml)
+            .map(Some)
+            .map_err(|message| Error::Parse { path, message })
+    }
+
+    /// the inherited rules are kept, unless `inherit` is disabled.
+    /// A rule replaces an inherited one with the same name
+    pub fn merge(&self, inherited: &[Rule]) -> Vec<Rule> {
+        let mut rules = if self.inherit {
+            inherited.to_vec()
+        } else {
+            Vec::new()
+        };
+        for rule in &self.rules {
+            let overridden = rule.name.as_ref().and_then(|name| {
+                rules
+                    .iter()
+                    .position(|inherited| inherited.name.as_ref() == Some(name))
+            });
+            match overridden {
+                Some(index) => rules[index] = rule.clone(),
+                None => rules.push(rule.clone()),
+            }
+        }
+        rules
+    }
+
+    pub fn parse(content: &str, format: Format) -> Result<Self, String> {
+        match format {
+            Format::Toml => toml::from_str(content).map_err(|error| error.to_string()),
+            Format::Yaml => serde_yaml::from_str(content).map_err(|error| error.to_string()),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use super::*;
+
+#[test]
+fn parse_toml() {
+    let rule_set = RuleSet::parse(
+        r#"
+[[rules]]
+name = "camera prefix"
+pattern = "^IMG_"
+types = ["file"]
+glob = "*.{jpg,JPG}"
+stop = true
+
+[[rules]]
+pattern = " "
+replacement = "_"
+"#,
+        Format::Toml,
+    )
+    .unwrap();
+
+    assert_eq!(
+        rule_set.rules,
+        vec![
+            Rule {
+                name: Some("camera prefix".to_string()),
+                pattern: "^IMG_".to_string(),
+                replacement: String::new(),
+                literal: false,
+                types: vec![FileKind::File],
+                glob: Some("*.{jpg,JPG}".to_string()),
+                stop: true,
+            },
+            Rule {
+                name: None,
+                pattern: " ".to_string(),
+                replacement: "_".to_string(),
+                literal: false,
+                types: Vec::new(),
+                glob: None,
+                stop: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_yaml() {
+    let rule_set = RuleSet::parse(
+        "
+rules:
+  - pattern: ' '
+    replacement: _
+    types: [dir, symlink]
+",
+        Format::Yaml,
+    )
+    .unwrap();
+
+    assert_eq!(
+        rule_set.rules[0].types,
+        vec![FileKind::Dir, FileKind::Symlink]
+    );
+}
+
+#[test]
+fn parse_unknown_field() {
+    assert_matches!(
+        RuleSet::parse("[[rules]]\npattern = 'a'\nreplace = 'b'\n", Format::Toml),
+        Err(_)
+    );
+}
+
+#[test]
+fn load_unknown_format() {
+    assert_matches!(
+        RuleSet::load(Path::new("rules.json")),
+        Err(Error::UnknownFormat(_))
+    );
+}
+
+#[test]
+fn load_missing_file() {
+    assert_matches!(
+        RuleSet::load(Path::new("non_existant/rules.toml")),
+        Err(Error::Io { .. })
+    );
+}
+
+fn rule(name: Option<&str>, pattern: &str) -> Rule {
+    Rule {
+        name: name.map(str::to_string),
+        pattern: pattern.to_string(),
+        replacement: String::new(),
+        literal: false,
+        types: Vec::new(),
+        glob: None,
+        stop: false,
+    }
+}
+
+#[test]
+fn merge_inherited_rules() {
+    let rule_set = RuleSet::parse(
+        "[[rules]]\nname = 'a'\npattern = 'A'\n\n[[rules]]\npattern = 'C'\n",
+        Format::Toml,
+    )
+    .unwrap();
+    let inherited = vec![rule(Some("a"), "a"), rule(Some("b"), "b")];
+
+    assert!(rule_set.inherit);
+    assert_eq!(
+        rule_set.merge(&inherited),
+        vec![rule(Some("a"), "A"), rule(Some("b"), "b"), rule(None, "C")]
+    );
+}
+
+#[test]
+fn merge_without_inheritance() {
+    let rule_set = RuleSet::parse(
+        "inherit = false\n\n[[rules]]\nname = 'a'\npattern = 'A'\n",
+        Format::Toml,
+    )
+    .unwrap();
+
+    assert_eq!(
+        rule_set.merge(&[rule(Some("b"), "b")]),
+        vec![rule(Some("a"), "A")]
+    );
+}
+
+#[test]
+fn load_dir_without_rules() {
+    assert_matches!(RuleSet::load_dir(Path::new("non_existant")), Ok(None));
+}
@@ -0,0 +1,65 @@
+use deunicode::deunicode_with_tofu;
+
+#[cfg(test)]
+#[path = "./slug_test.rs"]
+pub mod slug_test;
+
+/// transliterations which differ from the generic ones
+const TRANSLITERATIONS: &[(char, &str)] = &[
+    ('Ä', "Ae"),
+    ('Ö', "Oe"),
+    ('Ü', "Ue"),
+    ('ä', "ae"),
+    ('ö', "oe"),
+    ('ü', "ue"),
+    ('ß', "ss"),
+];
+
+/// characters which are removed without a separator
+const REMOVED: &[char] = &['\'', '"', '`', '´'];
+
+/// transliterates to ASCII
+pub fn transliterate(text: &str) -> String {
+    let mut ascii = String::with_capacity(text.len());
+    for c in text.chars() {
+        match TRANSLITERATIONS.iter().find(|(special, _)| *special == c) {
+            Some((_, transliteration)) => ascii.push_str(transliteration),
+            None => ascii.push_str(&deunicode_with_tofu(c.encode_utf8(&mut [0; 4]), "")),
+        }
+    }
+    ascii
+}
+
+/// transliterates and collapses everything except letters and digits into a single `separator`.
+/// The extension and the leading dot of a hidden file are kept apart.
+/// A stem without words stays unchanged, so that `___.jpg` doesn't become the hidden `.jpg`
+pub fn slugify(file_name: &str, separator: &str) -> String {
+    let hidden = file_name.len() - file_name.trim_start_matches('.').len();
+    let (hidden, name) = file_name.split_at(hidden.min(1));
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot != 0 => (&name[..dot], Some(&name[dot + 1..])),
+        _ => (name, None),
+    };
+
+    let mut slug = match slugify_part(stem, separator) {
+        slug if slug.is_empty() => format!("{}{}", hidden, stem),
+        slug => format!("{}{}", hidden, slug),
+    };
+    if let Some(extension) = extension.map(|extension| slugify_part(extension, separator)) {
+        if !extension.is_empty() {
+            slug.push('.');
+            slug.push_str(&extension);
+        }
+    }
+    slug
+}
+
+fn slugify_part(text: &str, separator: &str) -> String {
+    let ascii = transliterate(text);
+    ascii
+        .split(|c: char| !c.is_ascii_alphanumeric() && !REMOVED.contains(&c))
+        .map(|word| word.replace(REMOVED, ""))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
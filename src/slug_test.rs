@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn transliterate_to_ascii() {
+    assert_eq!(transliterate("Ärger"), "Aerger");
+    assert_eq!(transliterate("café"), "cafe");
+    assert_eq!(transliterate("Straße"), "Strasse");
+    assert_eq!(transliterate("plain"), "plain");
+}
+
+#[test]
+fn slugify_name() {
+    assert_eq!(slugify("Ärger im Café.txt", "-"), "Aerger-im-Cafe.txt");
+    assert_eq!(
+        slugify("  Hello,   World! (1).JPG", "-"),
+        "Hello-World-1.JPG"
+    );
+    assert_eq!(slugify("foo__bar--baz", "_"), "foo_bar_baz");
+}
+
+#[test]
+fn slugify_removes_quotes() {
+    assert_eq!(slugify("Don't stop.mp3", "-"), "Dont-stop.mp3");
+}
+
+#[test]
+fn slugify_keeps_hidden_dot() {
+    assert_eq!(slugify(".my config", "-"), ".my-config");
+    assert_eq!(slugify("..weird.name", "-"), ".weird.name");
+}
+
+#[test]
+fn slugify_without_extension() {
+    assert_eq!(slugify("Read Me", "-"), "Read-Me");
+    assert_eq!(slugify("archive.", "-"), "archive");
+}
+
+#[test]
+fn slugify_keeps_stem_without_words() {
+    assert_eq!(slugify("___.jpg", "-"), "___.jpg");
+    assert_eq!(slugify("!!!.TXT", "-"), "!!!.TXT");
+    assert_eq!(slugify("(((", "-"), "(((");
+}
@@ -1,11 +1,14 @@
 use crate::cli::Cli;
 use crate::fs::RenameInfo;
+use crate::replace::Step;
 use cli_table::{
     format::{Border, Justify, Separator},
     print_stdout, Cell as TableCell, Table,
 };
 use colored::Colorize;
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme, Severity};
 use std::cell::Cell;
+use std::fmt::Write;
 use terminal_size::{terminal_size, Width};
 
 #[cfg(test)]
@@ -30,6 +33,9 @@ macro_rules! add_info {
 
 pub struct Stats {
     show_renames: bool,
+    show_explanations: bool,
+    /// stdout only has the new names of `--stdin-names`
+    explain_to_stderr: bool,
     show_summary: bool,
     operation_mode: String,
     base_path: String,
@@ -50,6 +56,8 @@ impl Default for Stats {
     fn default() -> Self {
         Self {
             show_renames: false,
+            show_explanations: false,
+            explain_to_stderr: false,
             show_summary: false,
             operation_mode: String::new(),
             base_path: String::new(),
@@ -83,6 +91,8 @@ impl Stats {
 
     pub fn set_cli_opts(&mut self, opts: &Cli) {
         self.show_renames = opts.verbose >= 2;
+        self.show_explanations = opts.explain;
+        self.explain_to_stderr = opts.stdin_names;
         self.show_summary = opts.verbose >= 1;
         self.base_path = opts.base_path.to_string_lossy().to_string();
         if opts.run {
@@ -106,6 +116,23 @@ impl Stats {
         }
     }
 
+    /// errors and warnings of the patterns are shown with their position and a hint
+    pub fn diagnostic(&self, diagnostic: &dyn Diagnostic) {
+        let theme = if colored::control::SHOULD_COLORIZE.should_colorize() {
+            GraphicalTheme::unicode()
+        } else {
+            GraphicalTheme::unicode_nocolor()
+        };
+        let mut report = String::new();
+        match GraphicalReportHandler::new_themed(theme).render_report(&mut report, diagnostic) {
+            Ok(()) => eprint!("{}", report),
+            Err(_) => eprintln!("{}", diagnostic),
+        }
+        if self.show_summary && diagnostic.severity() != Some(Severity::Warning) {
+            self.errors.set(self.errors.get() + 1);
+        }
+    }
+
     pub fn rename(&self, rename_info: &RenameInfo) {
         if self.show_renames {
             let old_path = rename_info.old_file.path.to_string_lossy();
@@ -135,6 +162,49 @@ impl Stats {
         }
     }
 
+    /// shows the matches, groups and replacements of every rule, which matched the file.
+    /// With `--stdin-names` they are written to stderr
+    pub fn explain(&self, rename_info: &RenameInfo) {
+        if !self.show_explanations {
+            return;
+        }
+        if let Some(steps) = rename_info
+            .explanation
+            .as_deref()
+            .filter(|steps| !steps.is_empty())
+        {
+            let explanation = self.explanation(&rename_info.old_file.path.to_string_lossy(), steps);
+            if self.explain_to_stderr {
+                eprint!("{}", explanation);
+            } else {
+                print!("{}", explanation);
+            }
+        }
+    }
+
+    fn explanation(&self, old_path: &str, steps: &[Step]) -> String {
+        let mut text = format!("{}\n", old_path);
+        for step in steps {
+            let _ = writeln!(text, "  {}", step.pattern);
+            for found in &step.matches {
+                let _ = writeln!(
+                    text,
+                    "    {}..{} {:?}",
+                    found.span.start, found.span.end, found.text
+                );
+                for (label, value) in &found.groups {
+                    let _ = match value {
+                        Some(value) => writeln!(text, "      ${} = {:?}", label, value),
+                        None => writeln!(text, "      ${} is not matched", label),
+                    };
+                }
+                let _ = writeln!(text, "      {} {:?}", self.rename_arrow, found.replacement);
+            }
+            let _ = writeln!(text, "  {} {}", self.rename_arrow, step.result);
+        }
+        text
+    }
+
     fn has_output(&self) -> bool {
         self.errors.get() != 0
             || (self.show_renames
@@ -1,6 +1,7 @@
 use super::*;
 use crate::cli::cli_test::empty_cli;
 use crate::fs::{FileInfo, RenameInfo};
+use crate::replace::Match;
 use async_std::path::PathBuf;
 
 #[test]
@@ -70,6 +71,7 @@ fn not_count_file_without_summary() {
     stats.rename(&RenameInfo {
         old_file: FileInfo::file(PathBuf::new()),
         new_path: PathBuf::new(),
+        explanation: None,
     });
 
     assert_eq!(stats.errors.get(), 0);
@@ -86,6 +88,7 @@ fn count_file_with_summary() {
     stats.rename(&RenameInfo {
         old_file: FileInfo::file(PathBuf::new()),
         new_path: PathBuf::new(),
+        explanation: None,
     });
 
     assert_eq!(stats.errors.get(), 0);
@@ -126,3 +129,60 @@ fn has_output_as_renames() {
 
     assert!(stats.has_output());
 }
+
+#[test]
+fn count_diagnostic_errors() {
+    let mut stats = Stats::new();
+    stats.show_summary = true;
+
+    stats.diagnostic(&crate::replace::Warning::UnusedGroups {
+        pattern: "(a)".to_string(),
+    });
+    assert_eq!(stats.errors.get(), 0);
+
+    stats.diagnostic(&crate::replace::Error::DirSequence(".frsrules".into()));
+    assert_eq!(stats.errors.get(), 1);
+}
+
+#[test]
+fn explain_stdin_names_to_stderr() {
+    let mut cli_opts = empty_cli();
+    let mut stats = Stats::new();
+    stats.set_cli_opts(&cli_opts);
+    assert!(!stats.explain_to_stderr);
+
+    cli_opts.stdin_names = true;
+    stats.set_cli_opts(&cli_opts);
+    assert!(stats.explain_to_stderr);
+}
+
+#[test]
+fn explain_matches() {
+    let stats = Stats::default();
+    let steps = vec![Step {
+        pattern: r"(\d+)(?P<ext>\.\w+)?".to_string(),
+        matches: vec![Match {
+            span: 6..9,
+            text: "001".to_string(),
+            groups: vec![
+                ("1".to_string(), Some("001".to_string())),
+                ("ext".to_string(), None),
+            ],
+            replacement: "1".to_string(),
+        }],
+        result: "photo_1".to_string(),
+    }];
+
+    assert_eq!(
+        stats.explanation("./photo_001", &steps),
+        concat!(
+            "./photo_001\n",
+            "  (\\d+)(?P<ext>\\.\\w+)?\n",
+            "    6..9 \"001\"\n",
+            "      $1 = \"001\"\n",
+            "      $ext is not matched\n",
+            "      => \"1\"\n",
+            "  => photo_1\n",
+        )
+    );
+}
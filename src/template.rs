@@ -0,0 +1,591 @@
+use crate::case::Modifier;
+use crate::hash::Algorithm;
+use crate::matcher::{Groups, Matcher};
+use crate::media::{self, TagKind};
+use crate::random;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(test)]
+#[path = "./template_test.rs"]
+pub mod template_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("The format `{format}` of the placeholder `{placeholder}` is invalid")]
+    InvalidFormat { placeholder: String, format: String },
+    #[error("The placeholder `{0}` needs the name of a tag like `${{{0}:title}}`")]
+    MissingTagName(String),
+    #[error(transparent)]
+    Media(#[from] media::Error),
+}
+
+/// name of the sequence placeholder, if no capture group has the same name
+const SEQUENCE: &str = "n";
+
+/// name of the content hash placeholder
+const HASH: &str = "hash";
+
+/// length of `${rand}` without an explicit one
+const DEFAULT_RANDOM_LEN: usize = 8;
+
+/// format of the date placeholders without an explicit one
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Source of a date placeholder like `${mtime:%Y}`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateSource {
+    Modified,
+    Created,
+    Accessed,
+    Now,
+}
+
+impl DateSource {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mtime" => Some(Self::Modified),
+            "ctime" => Some(Self::Created),
+            "atime" => Some(Self::Accessed),
+            "now" => Some(Self::Now),
+            _ => None,
+        }
+    }
+}
+
+/// separator of the directories by `${relpath}` without an explicit one
+const DEFAULT_RELPATH_SEPARATOR: &str = "_";
+
+/// Source of a placeholder from the location of the file like `${parent}`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSource {
+    Parent,
+    Grandparent,
+    Depth,
+    RelPath,
+    Target,
+}
+
+impl PathSource {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "parent" => Some(Self::Parent),
+            "grandparent" => Some(Self::Grandparent),
+            "depth" => Some(Self::Depth),
+            "relpath" => Some(Self::RelPath),
+            "target" => Some(Self::Target),
+            _ => None,
+        }
+    }
+}
+
+/// Values of the placeholders for a single file
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub sequence: u64,
+    pub modified: Option<DateTime<FixedOffset>>,
+    pub created: Option<DateTime<FixedOffset>>,
+    pub accessed: Option<DateTime<FixedOffset>>,
+    pub now: Option<DateTime<FixedOffset>>,
+    /// name of the directory of the file
+    pub parent: Option<String>,
+    pub grandparent: Option<String>,
+    /// number of directories below the base path, which is 1 for its direct entries
+    pub depth: usize,
+    /// directories between the base path and the file
+    pub relpath: Vec<String>,
+    /// file name of the target of a symlink
+    pub target: Option<String>,
+    /// values of the random placeholders in the order of their appearance
+    pub random: Vec<String>,
+    /// hex digests of the content by the hash placeholders
+    pub hashes: HashMap<Algorithm, String>,
+    /// values of the embedded metadata placeholders
+    pub tags: HashMap<TagKind, HashMap<String, String>>,
+}
+
+/// A reference to a capture group of the search pattern
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupRef {
+    Index(usize),
+    Name(String),
+}
+
+impl GroupRef {
+    pub fn parse(name: &str) -> Self {
+        match name.parse() {
+            Ok(index) => Self::Index(index),
+            Err(_) => Self::Name(name.to_string()),
+        }
+    }
+
+    pub fn exists(&self, search: &dyn Matcher) -> bool {
+        let names = search.capture_names();
+        match self {
+            Self::Index(index) => *index < names.len(),
+            Self::Name(name) => names.contains(&Some(name.as_str())),
+        }
+    }
+}
+
+impl fmt::Display for GroupRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{}", index),
+            Self::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+/// An unbraced reference like `$1a`, which starts with the name of another group like `1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ambiguity {
+    pub reference: String,
+    pub group: String,
+}
+
+impl Ambiguity {
+    /// the braced form, which references the shorter group
+    pub fn suggestion(&self) -> String {
+        format!("${{{}}}{}", self.group, &self.reference[self.group.len()..])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Group {
+        group: GroupRef,
+        modifier: Option<Modifier>,
+    },
+    Sequence {
+        width: usize,
+    },
+    Date {
+        source: DateSource,
+        format: String,
+    },
+    /// the `separator` joins the directories of `${relpath}`
+    Path {
+        source: PathSource,
+        separator: String,
+    },
+    Random(random::Kind),
+    /// a `length` of zero inserts the whole digest
+    Hash {
+        algorithm: Algorithm,
+        length: usize,
+    },
+    Tag {
+        kind: TagKind,
+        tag: String,
+    },
+    /// `\U` or `\L` until `\E`
+    CaseStart(Modifier),
+    CaseEnd,
+    /// `\u` or `\l` for the next character
+    NextChar(Modifier),
+}
+
+/// A parsed replace pattern.
+/// It extends the syntax of `regex` with placeholders like `${n:03}` or `${mtime:%Y}`, case modifiers like
+/// `${1:upper}` and the sed-style `\U…\E`
+#[derive(Debug, Clone)]
+pub struct Template {
+    tokens: Vec<Token>,
+    /// index of the first random value of this template in the context
+    random_offset: usize,
+    ambiguities: Vec<Ambiguity>,
+}
+
+impl Template {
+    pub fn new(pattern: &str, search: &dyn Matcher) -> Result<Self, Error> {
+        let mut tokens = Vec::new();
+        let mut ambiguities = Vec::new();
+        let mut literal = String::new();
+        let mut rest = pattern;
+
+        while let Some(index) = rest.find(['$', '\\']) {
+            literal.push_str(&rest[..index]);
+            let special = char::from(rest.as_bytes()[index]);
+            rest = &rest[index + 1..];
+
+            if let Some(escaped) = rest.strip_prefix(special) {
+                literal.push(special);
+                rest = escaped;
+                continue;
+            }
+
+            let split = match special {
+                '$' => split_reference(rest)
+                    .map(|(reference, remaining)| {
+                        Ok::<_, Error>((Self::parse_reference(reference, search)?, remaining))
+                    })
+                    .transpose()?,
+                _ => split_escape(rest),
+            };
+            let (token, remaining) = match split {
+                Some(split) => split,
+                None => {
+                    literal.push(special);
+                    continue;
+                }
+            };
+            if let (Token::Group { .. }, false) = (&token, rest.starts_with('{')) {
+                let reference = &rest[..rest.len() - remaining.len()];
+                if let Some(group) = prefix_group(reference, search) {
+                    ambiguities.push(Ambiguity {
+                        reference: reference.to_string(),
+                        group: group.to_string(),
+                    });
+                }
+            }
+            rest = remaining;
+
+            if !literal.is_empty() {
+                tokens.push(Token::Literal(std::mem::take(&mut literal)));
+            }
+            tokens.push(token);
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(literal));
+        }
+
+        Ok(Self {
+            tokens,
+            random_offset: 0,
+            ambiguities,
+        })
+    }
+
+    /// the text is inserted as it is, without groups or placeholders
+    pub fn literal(text: &str) -> Self {
+        Self {
+            tokens: vec![Token::Literal(text.to_string())],
+            random_offset: 0,
+            ambiguities: Vec::new(),
+        }
+    }
+
+    /// the random values of the templates before this one are skipped
+    pub fn with_random_offset(mut self, random_offset: usize) -> Self {
+        self.random_offset = random_offset;
+        self
+    }
+
+    /// capture groups take precedence over placeholders with the same name
+    fn parse_reference(reference: &str, search: &dyn Matcher) -> Result<Token, Error> {
+        let (name, format) = match reference.split_once(':') {
+            Some((name, format)) => (name, Some(format)),
+            None => (reference, None),
+        };
+        let modifier = format.map(|format| format.parse::<Modifier>());
+
+        let is_group = search
+            .capture_names()
+            .into_iter()
+            .any(|group| group == Some(name));
+        if !(is_group && matches!(modifier, None | Some(Ok(_)))) {
+            if let Some(placeholder) = Self::parse_placeholder(name, format)? {
+                return Ok(placeholder);
+            }
+        }
+
+        Ok(Token::Group {
+            group: GroupRef::parse(name),
+            modifier: modifier
+                .transpose()
+                .map_err(|_| invalid_format(name, format.unwrap_or_default()))?,
+        })
+    }
+
+    fn parse_placeholder(name: &str, format: Option<&str>) -> Result<Option<Token>, Error> {
+        if name == SEQUENCE {
+            return Ok(Some(Token::Sequence {
+                width: parse_width(name, format.unwrap_or_default())?,
+            }));
+        }
+        if let Some(source) = DateSource::from_name(name) {
+            let format = format.unwrap_or(DEFAULT_DATE_FORMAT);
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(invalid_format(name, format));
+            }
+            return Ok(Some(Token::Date {
+                source,
+                format: format.to_string(),
+            }));
+        }
+        if let Some(source) = PathSource::from_name(name) {
+            let separator = match (source, format) {
+                (PathSource::RelPath, format) => format.unwrap_or(DEFAULT_RELPATH_SEPARATOR),
+                (_, None) => "",
+                (_, Some(format)) => return Err(invalid_format(name, format)),
+            };
+            return Ok(Some(Token::Path {
+                source,
+                separator: separator.to_string(),
+            }));
+        }
+        match (name, format) {
+            ("uuid", None) => return Ok(Some(Token::Random(random::Kind::Uuid))),
+            ("uuid", Some(format)) => return Err(invalid_format(name, format)),
+            ("rand", format) => {
+                let len = match parse_width(name, format.unwrap_or_default())? {
+                    0 => DEFAULT_RANDOM_LEN,
+                    len => len,
+                };
+                return Ok(Some(Token::Random(random::Kind::Chars(len))));
+            }
+            _ => (),
+        }
+        if name == HASH {
+            let (algorithm, length) = parse_hash_format(format.unwrap_or_default())?;
+            return Ok(Some(Token::Hash { algorithm, length }));
+        }
+        if let Some(kind) = TagKind::from_name(name) {
+            let tag = format
+                .filter(|tag| !tag.is_empty())
+                .ok_or_else(|| Error::MissingTagName(name.to_string()))?;
+            kind.check_supported()?;
+            return Ok(Some(Token::Tag {
+                kind,
+                tag: tag.to_string(),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// the template inserts a group or a placeholder, not only text and case changes
+    pub fn has_placeholders(&self) -> bool {
+        self.tokens.iter().any(|token| {
+            !matches!(
+                token,
+                Token::Literal(_) | Token::CaseStart(_) | Token::CaseEnd | Token::NextChar(_)
+            )
+        })
+    }
+
+    pub fn has_sequence(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, Token::Sequence { .. }))
+    }
+
+    /// the metadata of the file is needed by a date placeholder
+    pub fn has_metadata(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, Token::Date { source, .. } if *source != DateSource::Now))
+    }
+
+    /// a placeholder needs this location information of the file
+    pub fn has_path(&self, path_source: PathSource) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, Token::Path { source, .. } if *source == path_source))
+    }
+
+    /// the random placeholders in the order of their appearance
+    pub fn random_kinds(&self) -> impl Iterator<Item = random::Kind> + '_ {
+        self.tokens.iter().filter_map(|token| match token {
+            Token::Random(kind) => Some(*kind),
+            _ => None,
+        })
+    }
+
+    /// the algorithms of the content hash placeholders
+    pub fn hash_algorithms(&self) -> impl Iterator<Item = Algorithm> + '_ {
+        self.tokens.iter().filter_map(|token| match token {
+            Token::Hash { algorithm, .. } => Some(*algorithm),
+            _ => None,
+        })
+    }
+
+    /// the referenced capture groups
+    pub fn groups(&self) -> impl Iterator<Item = &GroupRef> {
+        self.tokens.iter().filter_map(|token| match token {
+            Token::Group { group, .. } => Some(group),
+            _ => None,
+        })
+    }
+
+    /// the unbraced references, which start with the name of another group
+    pub fn ambiguities(&self) -> &[Ambiguity] {
+        &self.ambiguities
+    }
+
+    /// the embedded metadata tags which are used by the placeholders
+    pub fn tags(&self) -> impl Iterator<Item = (TagKind, &str)> {
+        self.tokens.iter().filter_map(|token| match token {
+            Token::Tag { kind, tag } => Some((*kind, tag.as_str())),
+            _ => None,
+        })
+    }
+
+    pub fn expand(&self, captures: &dyn Groups, context: &Context) -> String {
+        let mut expansion = Expansion::default();
+        let mut random = context.random.iter().skip(self.random_offset);
+        for token in &self.tokens {
+            match token {
+                Token::Literal(literal) => expansion.push(literal),
+                Token::Group { group, modifier } => {
+                    let group = match group {
+                        GroupRef::Index(index) => captures.get(*index),
+                        GroupRef::Name(name) => captures.name(name),
+                    };
+                    let group = group.unwrap_or_default();
+                    match modifier {
+                        Some(modifier) => expansion.push(&modifier.apply(group)),
+                        None => expansion.push(group),
+                    }
+                }
+                Token::Sequence { width } => {
+                    expansion.push(&format!("{:0width$}", context.sequence, width = width))
+                }
+                Token::Date { source, format } => {
+                    let date = match source {
+                        DateSource::Modified => context.modified,
+                        DateSource::Created => context.created,
+                        DateSource::Accessed => context.accessed,
+                        DateSource::Now => context.now,
+                    };
+                    if let Some(date) = date {
+                        expansion.push(&date.format(format).to_string());
+                    }
+                }
+                Token::Path { source, separator } => match source {
+                    PathSource::Parent => {
+                        expansion.push(context.parent.as_deref().unwrap_or_default())
+                    }
+                    PathSource::Grandparent => {
+                        expansion.push(context.grandparent.as_deref().unwrap_or_default())
+                    }
+                    PathSource::Depth => expansion.push(&context.depth.to_string()),
+                    PathSource::RelPath => expansion.push(&context.relpath.join(separator)),
+                    PathSource::Target => {
+                        expansion.push(context.target.as_deref().unwrap_or_default())
+                    }
+                },
+                Token::Random(_) => {
+                    if let Some(value) = random.next() {
+                        expansion.push(value);
+                    }
+                }
+                Token::Hash { algorithm, length } => {
+                    if let Some(digest) = context.hashes.get(algorithm) {
+                        match length {
+                            0 => expansion.push(digest),
+                            length => expansion.push(&digest[..digest.len().min(*length)]),
+                        }
+                    }
+                }
+                Token::Tag { kind, tag } => {
+                    if let Some(value) = context.tags.get(kind).and_then(|tags| tags.get(tag)) {
+                        expansion.push(value);
+                    }
+                }
+                Token::CaseStart(modifier) => expansion.region = Some(*modifier),
+                Token::CaseEnd => expansion.region = None,
+                Token::NextChar(modifier) => expansion.next_char = Some(*modifier),
+            }
+        }
+        expansion.text
+    }
+}
+
+/// applies the sed-style case modifiers while expanding
+#[derive(Default)]
+struct Expansion {
+    text: String,
+    region: Option<Modifier>,
+    next_char: Option<Modifier>,
+}
+
+impl Expansion {
+    fn push(&mut self, text: &str) {
+        if self.region.is_none() && self.next_char.is_none() {
+            self.text.push_str(text);
+            return;
+        }
+        for c in text.chars() {
+            match self.next_char.take().or(self.region) {
+                Some(modifier) => self
+                    .text
+                    .push_str(&modifier.apply(c.encode_utf8(&mut [0; 4]))),
+                None => self.text.push(c),
+            }
+        }
+    }
+}
+
+/// splits a sed-style case modifier from the rest of the pattern (after `\\`)
+fn split_escape(pattern: &str) -> Option<(Token, &str)> {
+    let mut chars = pattern.chars();
+    let token = match chars.next()? {
+        'U' => Token::CaseStart(Modifier::Upper),
+        'L' => Token::CaseStart(Modifier::Lower),
+        'E' => Token::CaseEnd,
+        'u' => Token::NextChar(Modifier::Upper),
+        'l' => Token::NextChar(Modifier::Lower),
+        _ => return None,
+    };
+    Some((token, chars.as_str()))
+}
+
+/// splits a reference like `${name}` or `$name` from the rest of the pattern (after `$`)
+fn split_reference(pattern: &str) -> Option<(&str, &str)> {
+    if let Some(braced) = pattern.strip_prefix('{') {
+        let end = braced.find('}')?;
+        return Some((&braced[..end], &braced[end + 1..]));
+    }
+
+    let end = pattern
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(pattern.len());
+    (end != 0).then(|| pattern.split_at(end))
+}
+
+/// the longest group, with whose name the reference starts
+fn prefix_group<'a>(reference: &'a str, search: &dyn Matcher) -> Option<&'a str> {
+    (1..reference.len())
+        .rev()
+        .map(|end| &reference[..end])
+        .find(|prefix| GroupRef::parse(prefix).exists(search))
+}
+
+/// an empty format has no width and `03` or `3` pads with zeros to three digits
+fn parse_width(placeholder: &str, format: &str) -> Result<usize, Error> {
+    if format.is_empty() {
+        return Ok(0);
+    }
+    format
+        .parse()
+        .map_err(|_| invalid_format(placeholder, format))
+}
+
+/// the format is an optional algorithm (default SHA-256) followed by an optional length like `sha1:8` or `12`
+fn parse_hash_format(format: &str) -> Result<(Algorithm, usize), Error> {
+    let (algorithm, length) = match format.split_once(':') {
+        Some(split) => split,
+        None if format.bytes().all(|byte| byte.is_ascii_digit()) => ("", format),
+        None => (format, ""),
+    };
+    let algorithm = match algorithm {
+        "" => Algorithm::Sha256,
+        algorithm => algorithm
+            .parse()
+            .map_err(|_| invalid_format(HASH, format))?,
+    };
+    let length = parse_width(HASH, length).map_err(|_| invalid_format(HASH, format))?;
+    Ok((algorithm, length))
+}
+
+fn invalid_format(placeholder: &str, format: &str) -> Error {
+    Error::InvalidFormat {
+        placeholder: placeholder.to_string(),
+        format: format.to_string(),
+    }
+}
@@ -0,0 +1,385 @@
+use super::*;
+use regex::{Captures, Regex};
+
+fn expand_with(search: &str, replace: &str, name: &str, context: &Context) -> String {
+    let search = Regex::new(search).unwrap();
+    let template = Template::new(replace, &search).unwrap();
+    search
+        .replace_all(name, |captures: &Captures| {
+            template.expand(captures, context)
+        })
+        .into_owned()
+}
+
+fn expand(search: &str, replace: &str, name: &str, sequence: u64) -> String {
+    let context = Context {
+        sequence,
+        ..Default::default()
+    };
+    expand_with(search, replace, name, &context)
+}
+
+#[test]
+fn expand_groups_like_regex() {
+    assert_eq!(expand("(a)(b)", "$2$1", "ab", 0), "ba");
+    assert_eq!(expand("(a)(b)", "${2}_${1}", "ab", 0), "b_a");
+    assert_eq!(expand("(?P<x>a)", "<$x>", "ab", 0), "<a>b");
+    assert_eq!(expand("(a)", "$1b", "a", 0), "");
+    assert_eq!(expand("(a)", "$9", "a", 0), "");
+}
+
+#[test]
+fn expand_literal_dollars() {
+    assert_eq!(expand("a", "$$", "a", 0), "$");
+    assert_eq!(expand("a", "$", "a", 0), "$");
+    assert_eq!(expand("a", "${", "a", 0), "${");
+    assert_eq!(expand("a", "$-", "a", 0), "$-");
+}
+
+#[test]
+fn expand_literal_template() {
+    let search = Regex::new("(a)").unwrap();
+    let template = Template::literal(r"${1:upper}\U$n");
+    let captures = search.captures("a").unwrap();
+
+    assert_eq!(
+        template.expand(&captures, &Context::default()),
+        r"${1:upper}\U$n"
+    );
+    assert!(!template.has_sequence());
+}
+
+#[test]
+fn expand_sequence() {
+    assert_eq!(expand("(.+)", "${n}_$1", "foo", 7), "7_foo");
+    assert_eq!(expand("(.+)", "${n:03}_$1", "foo", 7), "007_foo");
+    assert_eq!(expand("(.+)", "$n", "foo", 1234), "1234");
+}
+
+#[test]
+fn prefer_group_named_like_sequence() {
+    assert_eq!(expand("(?P<n>.+)", "${n}", "foo", 7), "foo");
+    assert_eq!(expand("(?P<n>.+)", "${n:2}", "foo", 7), "07");
+}
+
+#[test]
+fn has_sequence() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert!(Template::new("${n:2}", &search).unwrap().has_sequence());
+    assert!(!Template::new("${1}", &search).unwrap().has_sequence());
+}
+
+#[test]
+fn invalid_sequence_format() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert_matches!(
+        Template::new("${n:abc}", &search),
+        Err(Error::InvalidFormat { .. })
+    );
+}
+
+#[test]
+fn expand_group_modifiers() {
+    assert_eq!(
+        expand("(.+)_(.+)", "${1:lower}_${2:upper}", "IMG_Foo", 0),
+        "img_FOO"
+    );
+    assert_eq!(
+        expand("(?P<name>.+)", "${name:title}", "hELLO wORLD", 0),
+        "Hello World"
+    );
+    assert_eq!(expand("(?P<n>.+)", "${n:upper}", "foo", 7), "FOO");
+}
+
+#[test]
+fn expand_sed_modifiers() {
+    assert_eq!(expand("(.+)", "\\L$1", "IMG_Foo.JPG", 0), "img_foo.jpg");
+    assert_eq!(expand("(.+)_(.+)", "\\U$1\\E_$2", "img_foo", 0), "IMG_foo");
+    assert_eq!(expand("(.+)", "\\L\\u$1", "fOO", 0), "Foo");
+    assert_eq!(expand("(.+)", "\\l$1", "FOO", 0), "fOO");
+}
+
+#[test]
+fn expand_escaped_backslashes() {
+    assert_eq!(expand("a", "\\\\U", "a", 0), "\\U");
+    assert_eq!(expand("a", "\\x", "a", 0), "\\x");
+    assert_eq!(expand("a", "\\", "a", 0), "\\");
+}
+
+#[test]
+fn invalid_group_modifier() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert_matches!(
+        Template::new("${1:snake}", &search),
+        Err(Error::InvalidFormat { .. })
+    );
+}
+
+#[test]
+fn expand_dates() {
+    let date = DateTime::parse_from_rfc3339("2021-03-04T05:06:07+01:00").unwrap();
+    let context = Context {
+        modified: Some(date),
+        created: Some(date),
+        accessed: Some(date),
+        now: Some(date),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        expand_with("(.+)", "${mtime}_$1", "foo", &context),
+        "2021-03-04_foo"
+    );
+    assert_eq!(
+        expand_with("(.+)", "${ctime:%H:%M}", "foo", &context),
+        "05:06"
+    );
+    assert_eq!(expand_with("(.+)", "$atime", "foo", &context), "2021-03-04");
+    assert_eq!(expand_with("(.+)", "${now:%Y}", "foo", &context), "2021");
+}
+
+#[test]
+fn has_metadata() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert!(Template::new("${mtime}", &search).unwrap().has_metadata());
+    assert!(!Template::new("${now}", &search).unwrap().has_metadata());
+    assert!(!Template::new("$1", &search).unwrap().has_metadata());
+}
+
+#[test]
+fn prefer_group_named_like_date() {
+    assert_eq!(expand("(?P<mtime>.+)", "${mtime}", "foo", 0), "foo");
+}
+
+#[test]
+fn invalid_date_format() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert_matches!(
+        Template::new("${mtime:%Q}", &search),
+        Err(Error::InvalidFormat { .. })
+    );
+}
+
+#[test]
+fn missing_tag_name() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert_matches!(
+        Template::new("${exif}", &search),
+        Err(Error::MissingTagName(_))
+    );
+    assert_matches!(
+        Template::new("${id3:}", &search),
+        Err(Error::MissingTagName(_))
+    );
+}
+
+#[cfg(not(feature = "media"))]
+#[test]
+fn tags_without_media_feature() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert_matches!(
+        Template::new("${exif:Model}", &search),
+        Err(Error::Media(media::Error::Unsupported(TagKind::Exif)))
+    );
+}
+
+#[cfg(feature = "media")]
+#[test]
+fn expand_tags() {
+    let search = Regex::new("(.+)").unwrap();
+    let template = Template::new("${id3:artist} - ${id3:title}", &search).unwrap();
+    let context = Context {
+        tags: hashmap! {
+            TagKind::Id3 => hashmap! {
+                "artist".to_string() => "Queen".to_string(),
+                "title".to_string() => "Bicycle Race".to_string(),
+            },
+        },
+        ..Default::default()
+    };
+
+    assert_eq!(
+        template.tags().collect::<Vec<_>>(),
+        vec![(TagKind::Id3, "artist"), (TagKind::Id3, "title")]
+    );
+    assert_eq!(
+        expand_with("(.+)", "${id3:artist} - ${id3:title}", "foo.mp3", &context),
+        "Queen - Bicycle Race"
+    );
+}
+
+#[test]
+fn expand_hash() {
+    let context = Context {
+        hashes: hashmap! {
+            Algorithm::Sha256 => "ba7816bf8f01cfea".to_string(),
+            Algorithm::Md5 => "900150983cd24fb0".to_string(),
+        },
+        ..Default::default()
+    };
+
+    assert_eq!(
+        expand_with("(.+)", "${hash:sha256:8}_$1", "foo", &context),
+        "ba7816bf_foo"
+    );
+    assert_eq!(expand_with("(.+)", "${hash:4}", "foo", &context), "ba78");
+    assert_eq!(
+        expand_with("(.+)", "${hash:md5}", "foo", &context),
+        "900150983cd24fb0"
+    );
+    assert_eq!(
+        expand_with("(.+)", "${hash:99}", "foo", &context),
+        "ba7816bf8f01cfea"
+    );
+}
+
+#[test]
+fn hash_algorithms() {
+    let search = Regex::new("(.+)").unwrap();
+    let template = Template::new("$hash${hash:sha1:8}", &search).unwrap();
+
+    assert_eq!(
+        template.hash_algorithms().collect::<Vec<_>>(),
+        vec![Algorithm::Sha256, Algorithm::Sha1]
+    );
+}
+
+#[test]
+fn invalid_hash_format() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert_matches!(
+        Template::new("${hash:crc32}", &search),
+        Err(Error::InvalidFormat { .. })
+    );
+    assert_matches!(
+        Template::new("${hash:sha1:x}", &search),
+        Err(Error::InvalidFormat { .. })
+    );
+}
+
+#[test]
+fn expand_path() {
+    let context = Context {
+        parent: Some("season1".to_string()),
+        grandparent: Some("show".to_string()),
+        depth: 3,
+        relpath: vec!["tv".to_string(), "show".to_string()],
+        target: Some("movie.mkv".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        expand_with("(.+)", "${grandparent} - ${parent} - $1", "01", &context),
+        "show - season1 - 01"
+    );
+    assert_eq!(
+        expand_with("(.+)", "${depth}_${relpath}", "01", &context),
+        "3_tv_show"
+    );
+    assert_eq!(
+        expand_with("(.+)", "${relpath: - }", "01", &context),
+        "tv - show"
+    );
+    assert_eq!(expand_with("(.+)", "$target", "01", &context), "movie.mkv");
+}
+
+#[test]
+fn has_path() {
+    let search = Regex::new("(.+)").unwrap();
+    let template = Template::new("${parent}$1", &search).unwrap();
+
+    assert!(template.has_path(PathSource::Parent));
+    assert!(!template.has_path(PathSource::Target));
+}
+
+#[test]
+fn invalid_path_format() {
+    let search = Regex::new("(.+)").unwrap();
+
+    assert_matches!(
+        Template::new("${parent:x}", &search),
+        Err(Error::InvalidFormat { .. })
+    );
+}
+
+#[test]
+fn expand_random() {
+    let search = Regex::new("(.+)").unwrap();
+    let template = Template::new("${rand}_${rand:4}_$uuid", &search).unwrap();
+    let context = Context {
+        random: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        template.random_kinds().collect::<Vec<_>>(),
+        vec![
+            random::Kind::Chars(8),
+            random::Kind::Chars(4),
+            random::Kind::Uuid
+        ]
+    );
+    assert_eq!(
+        expand_with("(.+)", "${rand}_${rand:4}_$uuid", "foo", &context),
+        "a_b_c"
+    );
+    assert_matches!(
+        Template::new("${uuid:4}", &search),
+        Err(Error::InvalidFormat { .. })
+    );
+}
+
+#[test]
+fn ambiguous_references() {
+    let ambiguities = |search: &str, replace: &str| {
+        let search = Regex::new(search).unwrap();
+        Template::new(replace, &search)
+            .unwrap()
+            .ambiguities()
+            .to_vec()
+    };
+
+    assert_eq!(
+        ambiguities("(a)", "$1a"),
+        vec![Ambiguity {
+            reference: "1a".to_string(),
+            group: "1".to_string(),
+        }]
+    );
+    assert_eq!(
+        ambiguities("(?P<a>.)(?P<ab>.)", "$ab"),
+        vec![Ambiguity {
+            reference: "ab".to_string(),
+            group: "a".to_string(),
+        }]
+    );
+    assert_eq!(ambiguities("(a)", "${1}a"), Vec::new());
+    assert_eq!(ambiguities("(a)", "$1-$n"), Vec::new());
+    assert_eq!(ambiguities("(a)", "$1_")[0].reference, "1_");
+    assert_eq!(
+        ambiguities("(a)", "$1a")[0].suggestion(),
+        "${1}a".to_string()
+    );
+}
+
+#[test]
+fn referenced_groups() {
+    let search = Regex::new("(?P<x>a)").unwrap();
+    let template = Template::new("$1${x:upper}$n", &search).unwrap();
+
+    assert_eq!(
+        template.groups().collect::<Vec<_>>(),
+        vec![&GroupRef::Index(1), &GroupRef::Name("x".to_string())]
+    );
+    assert!(GroupRef::Index(1).exists(&search));
+    assert!(!GroupRef::Index(2).exists(&search));
+    assert!(!GroupRef::Name("y".to_string()).exists(&search));
+}
@@ -2,5 +2,9 @@
 mod error_handler;
 
 pub mod select_map;
+#[cfg(test)]
+pub mod temp_dir;
 
 pub use select_map::{SelectMap, SelectMapExt};
+#[cfg(test)]
+pub use temp_dir::TempDir;
@@ -1,5 +1,6 @@
 use super::*;
 
+#[allow(dead_code)]
 #[path = "../../test-utils/select_map.rs"]
 mod select_map_utils;
 
@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, io, process};
+
+/// counts the directories of this process, so that parallel tests get different ones
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A unique directory for the files of a test, which is removed with all its content on drop
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// the directory is created anew, so that it never belongs to another process or user
+    pub fn new() -> Self {
+        loop {
+            let path = env::temp_dir().join(format!(
+                "frs-test-{}-{}-{:08x}",
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                rand::random::<u32>()
+            ));
+            match fs::create_dir(&path) {
+                Ok(()) => return Self { path },
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => (),
+                Err(error) => panic!("Couldn't create the test directory: {}", error),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+
+    /// the path of a new file in the directory with this content
+    pub fn file(&self, name: &str, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Default for TempDir {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
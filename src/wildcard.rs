@@ -0,0 +1,82 @@
+#[cfg(test)]
+#[path = "./wildcard_test.rs"]
+pub mod wildcard_test;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("The `{open}` in the glob `{glob}` is never closed")]
+    Unclosed { glob: String, open: char },
+}
+
+/// translates a shell glob like `*_*.jpg` to an anchored regex.
+/// Every wildcard becomes a capture group, so that it can be referenced as `$1`, `$2`, …:
+/// `*` matches as few characters as possible, `?` one character, `[…]` or `[!…]` one of a set
+/// and `{a,b}` one of the literal alternatives.
+/// A backslash escapes the next character
+pub fn to_regex(glob: &str) -> Result<String, Error> {
+    let unclosed = |open| Error::Unclosed {
+        glob: glob.to_string(),
+        open,
+    };
+    let mut regex = String::from("^");
+    let mut chars = glob.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str("(.*?)"),
+            '?' => regex.push_str("(.)"),
+            '[' => {
+                let mut set = String::new();
+                let mut closed = false;
+                for (index, c) in chars.by_ref().enumerate() {
+                    match c {
+                        // a leading `]` is part of the set
+                        ']' if index > 0 && set != "^" => {
+                            closed = true;
+                            break;
+                        }
+                        '!' | '^' if index == 0 => set.push('^'),
+                        '\\' | '[' | ']' | '&' | '~' => {
+                            set.push('\\');
+                            set.push(c);
+                        }
+                        c => set.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(unclosed('['));
+                }
+                regex.push_str(&format!("([{}])", set));
+            }
+            '{' => {
+                let mut alternatives = vec![String::new()];
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    match c {
+                        '}' => {
+                            closed = true;
+                            break;
+                        }
+                        ',' => alternatives.push(String::new()),
+                        c => alternatives.last_mut().unwrap().push(c),
+                    }
+                }
+                if !closed {
+                    return Err(unclosed('{'));
+                }
+                let alternatives: Vec<_> = alternatives
+                    .iter()
+                    .map(|alternative| regex::escape(alternative))
+                    .collect();
+                regex.push_str(&format!("({})", alternatives.join("|")));
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    regex.push_str(&regex::escape(&c.to_string()));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Ok(regex)
+}
@@ -0,0 +1,41 @@
+use super::*;
+use regex::Regex;
+
+fn captures(glob: &str, name: &str) -> Option<Vec<String>> {
+    let regex = Regex::new(&to_regex(glob).unwrap()).unwrap();
+    regex.captures(name).map(|captures| {
+        captures
+            .iter()
+            .skip(1)
+            .map(|capture| capture.unwrap().as_str().to_string())
+            .collect()
+    })
+}
+
+#[test]
+fn translate_wildcards() {
+    assert_eq!(to_regex("*_*.jpg").unwrap(), r"^(.*?)_(.*?)\.jpg$");
+    assert_eq!(to_regex("IMG?.png").unwrap(), r"^IMG(.)\.png$");
+    assert_eq!(to_regex("[!a-c]x").unwrap(), "^([^a-c])x$");
+    assert_eq!(to_regex("*.{jpg,jpeg}").unwrap(), r"^(.*?)\.(jpg|jpeg)$");
+    assert_eq!(to_regex(r"\*.txt").unwrap(), r"^\*\.txt$");
+}
+
+#[test]
+fn capture_wildcards() {
+    assert_eq!(
+        captures("*_*.jpg", "a_b_c.jpg"),
+        Some(vec!["a".to_string(), "b_c".to_string()])
+    );
+    assert_eq!(
+        captures("[]a]?.{md,txt}", "]1.md"),
+        Some(vec!["]".to_string(), "1".to_string(), "md".to_string()])
+    );
+    assert_eq!(captures("*.jpg", "a.jpg.bak"), None);
+}
+
+#[test]
+fn unclosed_brackets() {
+    assert_matches!(to_regex("[abc"), Err(Error::Unclosed { open: '[', .. }));
+    assert_matches!(to_regex("*.{jpg"), Err(Error::Unclosed { open: '{', .. }));
+}
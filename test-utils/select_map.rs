@@ -7,7 +7,7 @@ use std::time::Duration;
 pub async fn collected_single_recursive_stream(base: u32) -> Vec<u32> {
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 sleep(Duration::from_millis(21)).await;
                 Some(Box::new(stream::iter(
@@ -22,7 +22,7 @@ pub async fn collected_single_recursive_stream(base: u32) -> Vec<u32> {
 pub async fn collected_eliminated_single_recursive_stream(base: u32) -> Vec<u32> {
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 sleep(Duration::from_millis(21)).await;
                 if primary_num == base {
@@ -41,7 +41,7 @@ pub async fn collected_filtered_single_recursive_stream(base: u32) -> Vec<u32> {
     let maximal_filter = base / 2;
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 sleep(Duration::from_millis(21)).await;
                 if primary_num > maximal_filter {
@@ -59,12 +59,12 @@ pub async fn collected_filtered_single_recursive_stream(base: u32) -> Vec<u32> {
 pub async fn collected_non_sleeping_double_recursive_stream(base: u32) -> Vec<u32> {
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 Some(Box::new(
                     stream::iter(base * primary_num..base * primary_num + base).select_map(
                         move |secondary_num| {
-                            let secondary_num = secondary_num.clone();
+                            let secondary_num = *secondary_num;
                             Box::pin(async move {
                                 Some(Box::new(stream::iter(
                                     base * secondary_num..base * secondary_num + base,
@@ -84,13 +84,13 @@ pub async fn collected_double_recursive_stream(base: u32) -> Vec<u32> {
     const SECONDARY_TIME: Duration = Duration::from_millis(21);
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 sleep(PRIMARY_TIME).await;
                 Some(Box::new(
                     stream::iter(base * primary_num..base * primary_num + base).select_map(
                         move |secondary_num| {
-                            let secondary_num = secondary_num.clone();
+                            let secondary_num = *secondary_num;
                             Box::pin(async move {
                                 sleep(SECONDARY_TIME).await;
                                 Some(Box::new(stream::iter(
@@ -110,13 +110,13 @@ pub async fn collected_balanced_double_recursive_stream(base: u32) -> Vec<u32> {
     let primary_delay = base as u64 * 2;
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 sleep(Duration::from_millis(primary_delay - primary_num as u64)).await;
                 Some(Box::new(
                     stream::iter(base * primary_num..base * primary_num + base).select_map(
                         move |secondary_num| {
-                            let secondary_num = secondary_num.clone();
+                            let secondary_num = *secondary_num;
                             Box::pin(async move {
                                 sleep(Duration::from_millis(base as u64 + primary_num as u64))
                                     .await;
@@ -137,13 +137,13 @@ pub async fn collected_unbalanced_double_recursive_stream(base: u32) -> Vec<u32>
     let primary_delay = base as u64 * 2;
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 sleep(Duration::from_millis(primary_delay - primary_num as u64)).await;
                 Some(Box::new(
                     stream::iter(base * primary_num..base * primary_num + base).select_map(
                         move |secondary_num| {
-                            let secondary_num = secondary_num.clone();
+                            let secondary_num = *secondary_num;
                             Box::pin(async move {
                                 sleep(Duration::from_millis((base - primary_num) as u64)).await;
                                 Some(Box::new(stream::iter(
@@ -162,17 +162,17 @@ pub async fn collected_unbalanced_double_recursive_stream(base: u32) -> Vec<u32>
 pub async fn collected_non_sleeping_triple_recursive_stream(base: u32) -> Vec<u32> {
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 Some(Box::new(
                     stream::iter(base * primary_num..base * primary_num + base).select_map(
                         move |secondary_num| {
-                            let secondary_num = secondary_num.clone();
+                            let secondary_num = *secondary_num;
                             Box::pin(async move {
                                 Some(Box::new(
                                     stream::iter(base * secondary_num..base * secondary_num + base)
                                         .select_map(move |tertiary_num| {
-                                            let tertiary_num = tertiary_num.clone();
+                                            let tertiary_num = *tertiary_num;
                                             Box::pin(async move {
                                                 Some(Box::new(stream::iter(
                                                     base * tertiary_num..base * tertiary_num + base,
@@ -196,19 +196,19 @@ pub async fn collected_triple_recursive_stream(base: u32) -> Vec<u32> {
     const TERTIARY_TIME: Duration = Duration::from_millis(21);
     stream::iter(1..base)
         .select_map(|primary_num| {
-            let primary_num = primary_num.clone();
+            let primary_num = *primary_num;
             Box::pin(async move {
                 sleep(PRIMARY_TIME).await;
                 Some(Box::new(
                     stream::iter(base * primary_num..base * primary_num + base).select_map(
                         move |secondary_num| {
-                            let secondary_num = secondary_num.clone();
+                            let secondary_num = *secondary_num;
                             Box::pin(async move {
                                 sleep(SECONDARY_TIME).await;
                                 Some(Box::new(
                                     stream::iter(base * secondary_num..base * secondary_num + base)
                                         .select_map(move |tertiary_num| {
-                                            let tertiary_num = tertiary_num.clone();
+                                            let tertiary_num = *tertiary_num;
                                             Box::pin(async move {
                                                 sleep(TERTIARY_TIME).await;
                                                 Some(Box::new(stream::iter(